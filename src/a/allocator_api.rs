@@ -0,0 +1,110 @@
+use super::{AllocError, AllocResult, Allocator, MemoryResource, SyncAllocator};
+use allocator_api2::alloc as api;
+use core::{alloc::Layout, ptr::NonNull};
+
+fn to_slice(pointer: AllocResult<()>, layout: Layout) -> Result<NonNull<[u8]>, api::AllocError> {
+    pointer
+        .map(|p| NonNull::slice_from_raw_parts(p.cast::<u8>(), layout.size()))
+        .map_err(|_| api::AllocError)
+}
+
+fn as_block(pointer: NonNull<u8>) -> *mut () {
+    pointer.as_ptr() as *mut ()
+}
+
+/// Implements the `allocator_api` trait for an allocator handle.
+macro_rules! impl_allocator_api {
+    ($handle:ty) => {
+        unsafe impl api::Allocator for $handle {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, api::AllocError> {
+                // Safety: the block is only released through `deallocate` below
+                to_slice(unsafe { self.try_allocate_layout(layout) }, layout)
+            }
+
+            fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, api::AllocError> {
+                // Safety: the block is only released through `deallocate` below
+                to_slice(unsafe { self.allocate_zeroed_layout(layout) }, layout)
+            }
+
+            unsafe fn deallocate(&self, pointer: NonNull<u8>, layout: Layout) {
+                self.deallocate_layout(as_block(pointer), layout)
+            }
+
+            unsafe fn grow(
+                &self,
+                pointer: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, api::AllocError> {
+                let new_pointer = self.grow_layout(as_block(pointer), old_layout, new_layout);
+                to_slice(new_pointer, new_layout)
+            }
+
+            unsafe fn grow_zeroed(
+                &self,
+                pointer: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, api::AllocError> {
+                let new_pointer =
+                    self.grow_zeroed_layout(as_block(pointer), old_layout, new_layout);
+                to_slice(new_pointer, new_layout)
+            }
+
+            unsafe fn shrink(
+                &self,
+                pointer: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, api::AllocError> {
+                let new_pointer = self.shrink_layout(as_block(pointer), old_layout, new_layout);
+                to_slice(new_pointer, new_layout)
+            }
+        }
+    };
+}
+
+// Lets `Vec<T, Allocator>`, `Box<T, Allocator>` and friends allocate from a memory resource.
+impl_allocator_api!(Allocator);
+impl_allocator_api!(SyncAllocator);
+
+/// # An `allocator_api` allocator as a memory resource
+///
+/// The opposite direction of the `Allocator` bridge, e.g. `AllocatorApiResource::new(Global)`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AllocatorApiResource<A>(A);
+
+impl<A> AllocatorApiResource<A> {
+    pub const fn new(alloc: A) -> Self {
+        Self(alloc)
+    }
+
+    pub fn get(&self) -> &A {
+        &self.0
+    }
+}
+
+impl<A> MemoryResource for AllocatorApiResource<A>
+where
+    A: api::Allocator,
+{
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        self.0
+            .allocate(layout)
+            .map(NonNull::cast)
+            .map_err(|_| AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn allocate_zeroed(&mut self, layout: Layout) -> AllocResult<()> {
+        self.0
+            .allocate_zeroed(layout)
+            .map(NonNull::cast)
+            .map_err(|_| AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        if let Some(pointer) = NonNull::new(pointer as *mut u8) {
+            self.0.deallocate(pointer, layout)
+        }
+    }
+}
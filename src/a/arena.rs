@@ -0,0 +1,200 @@
+use super::{bump::Bump, AllocError, AllocResult, MemoryResource, Rewind};
+use alloc::boxed::Box;
+use core::{alloc::Layout, marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
+
+/// A position in an arena to [`rewind`](Rewind::rewind) to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArenaCheckpoint {
+    used: usize,
+}
+
+/// # A bump allocator over a borrowed buffer
+///
+/// Allocations are carved from the front of the buffer, aligned on their actual address so any
+/// alignment works, even one above the buffer's own; the padding counts as used. Deallocation
+/// does nothing, [`reset`](Self::reset) frees everything at once. Once the buffer is full
+/// allocations fail with [`AllocErrorKind::Exhausted`](super::AllocErrorKind::Exhausted).
+///
+/// An [`Allocator`](super::Allocator) needs a `'static` resource, so a buffer on the stack is
+/// used directly through [`MemoryResource`]; [`ArrayArenaResource`] owns its buffer instead.
+#[derive(Debug)]
+pub struct ArenaResource<'a> {
+    bump: Bump,
+    _buffer: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> ArenaResource<'a> {
+    pub fn new(buffer: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            bump: Bump::new(buffer.as_mut_ptr() as *mut u8, buffer.len()),
+            _buffer: PhantomData,
+        }
+    }
+
+    /// Bytes handed out so far, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.bump.used()
+    }
+
+    /// Bytes left; an aligned request may need some of them for padding.
+    pub fn remaining(&self) -> usize {
+        self.bump.remaining()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bump.capacity()
+    }
+
+    /// Frees every allocation, invalidating them.
+    ///
+    /// # Safety
+    /// No allocation made so far may be used afterwards.
+    pub unsafe fn reset(&mut self) {
+        self.bump.reset()
+    }
+}
+
+impl Rewind for ArenaResource<'_> {
+    type Checkpoint = ArenaCheckpoint;
+
+    fn checkpoint(&self) -> ArenaCheckpoint {
+        ArenaCheckpoint {
+            used: self.bump.used(),
+        }
+    }
+
+    unsafe fn rewind(&mut self, checkpoint: ArenaCheckpoint) {
+        self.bump.rewind(checkpoint.used)
+    }
+}
+
+impl<'a> From<&'a mut [u8]> for ArenaResource<'a> {
+    fn from(buffer: &'a mut [u8]) -> Self {
+        Self {
+            bump: Bump::new(buffer.as_mut_ptr(), buffer.len()),
+            _buffer: PhantomData,
+        }
+    }
+}
+
+impl MemoryResource for ArenaResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        self.bump
+            .allocate(layout)
+            .map(|pointer| pointer.cast())
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate(&mut self, _pointer: *mut (), _layout: Layout) {}
+
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        self.bump
+            .try_grow_last(pointer as *const u8, old_layout.size(), new_layout.size())
+    }
+}
+
+/// # A bump allocator owning a `[u8; N]`
+///
+/// Works like [`ArenaResource`], with the array boxed and owned so that it can live behind an
+/// [`Allocator`](super::Allocator). The box is turned into a pointer once, on construction, and
+/// every allocation is carved through it, so moving or borrowing the resource leaves blocks
+/// already handed out valid.
+#[derive(Debug)]
+pub struct ArrayArenaResource<const N: usize> {
+    data: NonNull<[u8; N]>,
+    bump: Bump,
+}
+
+impl<const N: usize> ArrayArenaResource<N> {
+    /// An arena over a zeroed array.
+    pub fn new() -> Self {
+        Self::from_box(Box::new([0; N]))
+    }
+
+    pub fn from_array(data: [u8; N]) -> Self {
+        Self::from_box(Box::new(data))
+    }
+
+    pub fn from_box(data: Box<[u8; N]>) -> Self {
+        let data = NonNull::from(Box::leak(data));
+        Self {
+            data,
+            bump: Bump::new(data.as_ptr() as *mut u8, N),
+        }
+    }
+
+    /// Bytes handed out so far, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.bump.used()
+    }
+
+    /// Bytes left; an aligned request may need some of them for padding.
+    pub fn remaining(&self) -> usize {
+        self.bump.remaining()
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Frees every allocation, invalidating them.
+    ///
+    /// # Safety
+    /// No allocation made so far may be used afterwards.
+    pub unsafe fn reset(&mut self) {
+        self.bump.reset()
+    }
+}
+
+impl<const N: usize> Rewind for ArrayArenaResource<N> {
+    type Checkpoint = ArenaCheckpoint;
+
+    fn checkpoint(&self) -> ArenaCheckpoint {
+        ArenaCheckpoint {
+            used: self.bump.used(),
+        }
+    }
+
+    unsafe fn rewind(&mut self, checkpoint: ArenaCheckpoint) {
+        self.bump.rewind(checkpoint.used)
+    }
+}
+
+impl<const N: usize> Default for ArrayArenaResource<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> MemoryResource for ArrayArenaResource<N> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        self.bump
+            .allocate(layout)
+            .map(|pointer| pointer.cast())
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate(&mut self, _pointer: *mut (), _layout: Layout) {}
+
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        self.bump
+            .try_grow_last(pointer as *const u8, old_layout.size(), new_layout.size())
+    }
+}
+
+impl<const N: usize> Drop for ArrayArenaResource<N> {
+    fn drop(&mut self) {
+        // Safety: the pointer came from a box in `from_box`
+        drop(unsafe { Box::from_raw(self.data.as_ptr()) });
+    }
+}
@@ -0,0 +1,239 @@
+use super::{AllocError, AllocResult, MemoryResource};
+use core::{
+    alloc::Layout,
+    fmt,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::NonNull,
+    slice,
+};
+
+const BITS: usize = usize::BITS as usize;
+
+/// # A region of equal blocks tracked by a bitmap
+///
+/// Splits one contiguous region into blocks of a power of two size and hands out runs of them,
+/// first fit. The occupancy bitmap is carved from the front of the region itself, so no other
+/// memory is needed and nothing is ever written into free blocks, which suits a `static` buffer
+/// on a target without a heap. Blocks are aligned to their size; larger alignments are found by
+/// searching only suitably aligned runs.
+///
+/// Requests are rounded up to whole blocks, and a request larger than the whole region fails
+/// with [`AllocErrorKind::UnsupportedSize`](super::AllocErrorKind::UnsupportedSize).
+#[derive(Debug)]
+pub struct BitmapResource<'a> {
+    bitmap: *mut usize,
+    blocks: *mut u8,
+    block_size: usize,
+    block_count: usize,
+    _region: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> BitmapResource<'a> {
+    /// Splits `region` into as many blocks of `block_size` bytes as fit beside their bitmap.
+    ///
+    /// # Panics
+    /// If `block_size` is not a power of two.
+    pub fn new(region: &'a mut [MaybeUninit<u8>], block_size: usize) -> Self {
+        assert!(
+            block_size.is_power_of_two(),
+            "block size must be a power of two"
+        );
+        let start = region.as_mut_ptr() as *mut u8;
+        let len = region.len();
+        let bitmap_offset = start.align_offset(mem::align_of::<usize>());
+        // where the blocks start if there are `count` of them, if they fit
+        let blocks_offset = |count: usize| {
+            let bitmap_end = bitmap_offset + count.div_ceil(BITS) * mem::size_of::<usize>();
+            let blocks_offset =
+                bitmap_end + start.wrapping_add(bitmap_end).align_offset(block_size);
+            Some(blocks_offset).filter(|offset| offset + count * block_size <= len)
+        };
+        // every block costs its size and a bit; the estimate is then trimmed by the padding
+        let mut count = len / block_size;
+        count = count.min(len.saturating_mul(8) / (8 * block_size + 1));
+        while count > 0 && blocks_offset(count).is_none() {
+            count -= 1;
+        }
+        let (bitmap, blocks) = match blocks_offset(count) {
+            Some(offset) if count > 0 => {
+                let bitmap = start.wrapping_add(bitmap_offset) as *mut usize;
+                // Safety: the bitmap lies within the region, before the blocks
+                unsafe { bitmap.write_bytes(0, count.div_ceil(BITS)) };
+                (bitmap, start.wrapping_add(offset))
+            }
+            _ => (NonNull::dangling().as_ptr(), start),
+        };
+        Self {
+            bitmap,
+            blocks,
+            block_size,
+            block_count: count,
+            _region: PhantomData,
+        }
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.block_count
+    }
+
+    pub fn used_blocks(&self) -> usize {
+        self.words()
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether `pointer` lies in this resource's blocks: two comparisons, no lookup.
+    pub fn owns(&self, pointer: *const ()) -> bool {
+        let address = pointer as usize;
+        let start = self.blocks as usize;
+        address >= start && address < start + self.block_count * self.block_size
+    }
+
+    /// The occupancy of every block, printable for debugging.
+    pub fn bitmap(&self) -> Bitmap<'_> {
+        Bitmap {
+            words: self.words(),
+            len: self.block_count,
+        }
+    }
+
+    fn words(&self) -> &[usize] {
+        // Safety: the bitmap was initialized in `new`, and is dangling only when empty
+        unsafe { slice::from_raw_parts(self.bitmap, self.block_count.div_ceil(BITS)) }
+    }
+
+    fn is_used(&self, index: usize) -> bool {
+        self.words()[index / BITS] >> (index & (BITS - 1)) & 1 == 1
+    }
+
+    fn set_run(&mut self, start: usize, len: usize, used: bool) {
+        for index in start..start + len {
+            // Safety: the caller keeps runs within the bitmap
+            let word = unsafe { &mut *self.bitmap.add(index / BITS) };
+            let bit = 1 << (index & (BITS - 1));
+            debug_assert_ne!(
+                *word & bit != 0,
+                used,
+                "block {} already in that state",
+                index
+            );
+            if used {
+                *word |= bit;
+            } else {
+                *word &= !bit;
+            }
+        }
+    }
+
+    /// The blocks a request of `size` bytes spans.
+    fn blocks_for(&self, size: usize) -> usize {
+        size.div_ceil(self.block_size).max(1)
+    }
+
+    /// The first free run of `len` blocks whose address is aligned to `align`.
+    fn find_run(&self, len: usize, align: usize) -> Option<usize> {
+        let stride = (align / self.block_size).max(1);
+        // the blocks start aligned to their size, so the first aligned one is a whole number
+        // of blocks in, and every `stride`th after it
+        let first = ((self.blocks as usize).wrapping_neg() & (align - 1)) / self.block_size;
+        let aligned_from =
+            |index: usize| first + index.saturating_sub(first).div_ceil(stride) * stride;
+        let mut start = first;
+        while start + len <= self.block_count {
+            match (start..start + len)
+                .rev()
+                .find(|&index| self.is_used(index))
+            {
+                None => return Some(start),
+                Some(used) => start = aligned_from(used + 1),
+            }
+        }
+        None
+    }
+}
+
+impl MemoryResource for BitmapResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        let len = self.blocks_for(layout.size());
+        if len > self.block_count {
+            return Err(AllocError::unsupported_size::<Self>(layout));
+        }
+        let start = self
+            .find_run(len, layout.align())
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))?;
+        self.set_run(start, len, true);
+        Ok(NonNull::new_unchecked(
+            self.blocks.add(start * self.block_size) as *mut MaybeUninit<()>,
+        ))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        debug_assert!(self.owns(pointer), "pointer not from this resource");
+        let start = (pointer as usize - self.blocks as usize) / self.block_size;
+        self.set_run(start, self.blocks_for(layout.size()), false);
+    }
+
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        let start = (pointer as usize - self.blocks as usize) / self.block_size;
+        let (old_end, new_end) = (
+            start + self.blocks_for(old_layout.size()),
+            start + self.blocks_for(new_layout.size()),
+        );
+        if new_end > self.block_count || (old_end..new_end).any(|index| self.is_used(index)) {
+            return false;
+        }
+        self.set_run(old_end, new_end - old_end, true);
+        true
+    }
+}
+
+/// # A view of a [`BitmapResource`]'s occupancy
+///
+/// Displays as one character per block, `#` when used and `.` when free, 64 to a line.
+#[derive(Clone, Copy, Debug)]
+pub struct Bitmap<'r> {
+    words: &'r [usize],
+    len: usize,
+}
+
+impl Bitmap<'_> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether block `index` is in use.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn is_used(&self, index: usize) -> bool {
+        assert!(index < self.len, "block {} out of bounds", index);
+        self.words[index / BITS] >> (index & (BITS - 1)) & 1 == 1
+    }
+}
+
+impl fmt::Display for Bitmap<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for index in 0..self.len {
+            if index > 0 && index & 63 == 0 {
+                f.write_str("\n")?;
+            }
+            f.write_str(if self.is_used(index) { "#" } else { "." })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,358 @@
+use super::{AllocError, AllocResult, Allocator, MemoryResource};
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+/// Marks a block's metadata byte as free; the low bits hold its order.
+const FREE: u8 = 0x80;
+const ORDERS: usize = usize::BITS as usize;
+
+struct FreeBlock {
+    prev: *mut FreeBlock,
+    next: *mut FreeBlock,
+}
+
+/// Header at the base of every tree, followed by one metadata byte per minimum block.
+struct Tree {
+    next: Option<NonNull<Tree>>,
+    /// Bytes from the base covered by blocks, a multiple of the minimum block.
+    span: usize,
+    max_order: u32,
+    /// The upstream layout, for trees taken from upstream.
+    layout: Option<Layout>,
+}
+
+impl Tree {
+    unsafe fn base(tree: *mut Tree) -> *mut u8 {
+        tree as *mut u8
+    }
+
+    unsafe fn meta(tree: *mut Tree) -> *mut u8 {
+        (tree as *mut u8).add(mem::size_of::<Tree>())
+    }
+}
+
+/// How a [`BuddyResource`]'s memory is used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BuddyStats {
+    /// Bytes that can be handed out, excluding metadata.
+    pub capacity: usize,
+    /// Bytes in allocated blocks.
+    pub allocated: usize,
+    /// Bytes actually requested for those blocks.
+    pub requested: usize,
+    /// The largest free block.
+    pub largest_free: usize,
+}
+
+impl BuddyStats {
+    /// Bytes lost to rounding requests up to a power of two.
+    pub fn internal_fragmentation(&self) -> usize {
+        self.allocated - self.requested
+    }
+
+    pub fn free(&self) -> usize {
+        self.capacity - self.allocated
+    }
+}
+
+/// # A power of two buddy allocator
+///
+/// Every block is a power of two, at least the configurable minimum. Allocation splits a larger
+/// free block in halves until one fits, freeing merges a block with its free buddy repeatedly;
+/// both take time logarithmic in the size of the memory. The rounding up bounds fragmentation,
+/// and is reported by [`stats`](Self::stats).
+///
+/// Memory comes either from one fixed region or from chunks taken from an upstream allocator as
+/// needed, each aligned to its size. Either way a header and one byte per minimum block are
+/// kept at the front, and free blocks link themselves into per order lists.
+pub struct BuddyResource<'a> {
+    min_order: u32,
+    /// Order of the upstream chunks, zero for a region.
+    chunk_order: u32,
+    /// The largest alignment every block of enough size has.
+    max_align: usize,
+    free: [*mut FreeBlock; ORDERS],
+    trees: Option<NonNull<Tree>>,
+    upstream: Option<Allocator>,
+    stats: BuddyStats,
+    _region: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+/// The smallest order whose blocks can hold the free list links.
+fn smallest_order() -> u32 {
+    mem::size_of::<FreeBlock>().trailing_zeros()
+}
+
+fn order_of(size: usize) -> Option<u32> {
+    Some(size.checked_next_power_of_two()?.trailing_zeros())
+}
+
+impl<'a> BuddyResource<'a> {
+    /// Manages `region` in blocks of at least `2^min_order` bytes, raised to hold two pointers.
+    pub fn new(region: &'a mut [MaybeUninit<u8>], min_order: u32) -> Self {
+        let mut resource = Self::empty(min_order, 0, None);
+        let min_block = 1 << resource.min_order;
+        let start = region.as_mut_ptr() as *mut u8;
+        let offset = start.align_offset(min_block);
+        let span = region.len().saturating_sub(offset) & !(min_block - 1);
+        if span > 0 {
+            let base = start.wrapping_add(offset);
+            let max_order = order_of(span).expect("a region's size has a next power of two");
+            // Safety: the tree lies within the region
+            if unsafe { resource.add_tree(base, span, max_order, None) } {
+                resource.max_align = 1 << (base as usize).trailing_zeros().min(max_order);
+            }
+        }
+        resource
+    }
+
+    pub fn min_order(&self) -> u32 {
+        self.min_order
+    }
+
+    pub fn stats(&self) -> BuddyStats {
+        let largest_free = (0..ORDERS)
+            .rev()
+            .find(|&order| !self.free[order].is_null())
+            .map_or(0, |order| 1 << order);
+        BuddyStats {
+            largest_free,
+            ..self.stats
+        }
+    }
+
+    fn empty(min_order: u32, chunk_order: u32, upstream: Option<Allocator>) -> Self {
+        Self {
+            min_order: min_order.max(smallest_order()),
+            chunk_order,
+            max_align: 1 << chunk_order,
+            free: [ptr::null_mut(); ORDERS],
+            trees: None,
+            upstream,
+            stats: BuddyStats::default(),
+            _region: PhantomData,
+        }
+    }
+
+    /// Sets up a tree at `base`, and frees every block past its metadata. Returns `false`, doing
+    /// nothing, if the metadata leaves no room for a block.
+    unsafe fn add_tree(
+        &mut self,
+        base: *mut u8,
+        span: usize,
+        max_order: u32,
+        layout: Option<Layout>,
+    ) -> bool {
+        let meta_len = span >> self.min_order;
+        let min_block = 1 << self.min_order;
+        let first = (mem::size_of::<Tree>() + meta_len + min_block - 1) & !(min_block - 1);
+        if first >= span {
+            return false;
+        }
+        let tree = base as *mut Tree;
+        tree.write(Tree {
+            next: self.trees,
+            span,
+            max_order,
+            layout,
+        });
+        self.trees = NonNull::new(tree);
+        Tree::meta(tree).write_bytes(0, meta_len);
+        let mut offset = first;
+        while offset < span {
+            let mut order = offset.trailing_zeros().min(max_order);
+            while offset + (1 << order) > span {
+                order -= 1;
+            }
+            self.push(tree, offset, order);
+            self.stats.capacity += 1 << order;
+            offset += 1 << order;
+        }
+        true
+    }
+
+    /// The tree holding `block`.
+    fn tree_of(&self, block: *mut u8) -> *mut Tree {
+        if self.upstream.is_some() {
+            // chunks are aligned to their size, so masking finds the header; a freed block's
+            // pointer doesn't reach it, so the chunk provenance exposed by `add_chunk` is used
+            ptr::with_exposed_provenance_mut(block as usize & !((1 << self.chunk_order) - 1))
+        } else {
+            self.trees
+                .expect("a region with blocks has its tree")
+                .as_ptr()
+        }
+    }
+
+    unsafe fn push(&mut self, tree: *mut Tree, offset: usize, order: u32) {
+        let block = Tree::base(tree).add(offset) as *mut FreeBlock;
+        let head = &mut self.free[order as usize];
+        block.write(FreeBlock {
+            prev: ptr::null_mut(),
+            next: *head,
+        });
+        if !head.is_null() {
+            (**head).prev = block;
+        }
+        *head = block;
+        *Tree::meta(tree).add(offset >> self.min_order) = FREE | order as u8;
+    }
+
+    unsafe fn unlink(&mut self, block: *mut FreeBlock, order: u32) {
+        let FreeBlock { prev, next } = block.read();
+        if prev.is_null() {
+            self.free[order as usize] = next;
+        } else {
+            (*prev).next = next;
+        }
+        if !next.is_null() {
+            (*next).prev = prev;
+        }
+    }
+
+    /// Takes a free block of `order`, splitting a larger one if needed.
+    unsafe fn take(&mut self, order: u32) -> Option<*mut u8> {
+        let mut found = (order as usize..ORDERS).find(|&o| !self.free[o].is_null())? as u32;
+        let block = self.free[found as usize];
+        self.unlink(block, found);
+        let tree = self.tree_of(block as *mut u8);
+        let offset = block as usize - Tree::base(tree) as usize;
+        while found > order {
+            found -= 1;
+            self.push(tree, offset + (1 << found), found);
+        }
+        *Tree::meta(tree).add(offset >> self.min_order) = order as u8;
+        Some(block as *mut u8)
+    }
+
+    unsafe fn add_chunk(&mut self) -> Result<(), AllocError> {
+        let size = 1 << self.chunk_order;
+        let layout = Layout::from_size_align(size, size)
+            .map_err(|_| AllocError::capacity_overflow::<Self>())?;
+        let upstream = self.upstream.as_ref().expect("chunks come from upstream");
+        let base = upstream.try_allocate_layout(layout)?.as_ptr() as *mut u8;
+        base.expose_provenance();
+        let added = self.add_tree(base, size, self.chunk_order, Some(layout));
+        debug_assert!(added, "chunk sizes are checked on construction");
+        Ok(())
+    }
+}
+
+impl BuddyResource<'static> {
+    /// Takes chunks of `2^chunk_order` bytes from `upstream` as needed, split into blocks of at
+    /// least `2^min_order` bytes.
+    ///
+    /// # Panics
+    /// If a chunk can't hold its metadata and at least one block.
+    pub fn with_upstream(chunk_order: u32, min_order: u32, upstream: Allocator) -> Self {
+        let resource = Self::empty(min_order, chunk_order, Some(upstream));
+        let min_order = resource.min_order;
+        assert!(
+            min_order < chunk_order && chunk_order < usize::BITS,
+            "chunk too small for its metadata"
+        );
+        let meta_len = 1 << (chunk_order - min_order);
+        assert!(
+            mem::size_of::<Tree>() + meta_len <= (1 << chunk_order) - (1 << min_order),
+            "chunk too small for its metadata"
+        );
+        resource
+    }
+
+    pub fn upstream(&self) -> Option<&Allocator> {
+        self.upstream.as_ref()
+    }
+}
+
+impl BuddyResource<'_> {
+    /// Returns every chunk to upstream, invalidating all allocations. Does nothing for a region.
+    ///
+    /// # Safety
+    /// No block handed out so far may be used afterwards.
+    pub unsafe fn release(&mut self) {
+        let upstream = match &self.upstream {
+            Some(upstream) => upstream,
+            None => return,
+        };
+        let mut tree = self.trees.take();
+        while let Some(current) = tree {
+            // the chunk was allocated from upstream with the layout in its header
+            let Tree { next, layout, .. } = current.as_ptr().read();
+            upstream.deallocate_layout(
+                current.as_ptr() as *mut (),
+                layout.expect("chunks record their layout"),
+            );
+            tree = next;
+        }
+        self.free = [ptr::null_mut(); ORDERS];
+        self.stats = BuddyStats::default();
+    }
+}
+
+impl MemoryResource for BuddyResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        let order = order_of(layout.size().max(layout.align()))
+            .ok_or_else(|| AllocError::unsupported_size::<Self>(layout))?
+            .max(self.min_order);
+        // a chunk's header takes its first block, so chunks can't hand out a whole one
+        let limit = match self.upstream {
+            Some(_) => self.chunk_order,
+            None => self.trees.map_or(0, |tree| tree.as_ref().max_order + 1),
+        };
+        if order >= limit {
+            return Err(AllocError::unsupported_size::<Self>(layout));
+        }
+        if layout.align() > self.max_align {
+            return Err(AllocError::misaligned::<Self>(layout));
+        }
+        let block = match self.take(order) {
+            Some(block) => block,
+            None if self.upstream.is_some() => {
+                self.add_chunk()?;
+                self.take(order).expect("a new chunk has free blocks")
+            }
+            None => return Err(AllocError::exhausted::<Self>(layout)),
+        };
+        self.stats.allocated += 1 << order;
+        self.stats.requested += layout.size();
+        Ok(NonNull::new_unchecked(block as *mut MaybeUninit<()>))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        let tree = self.tree_of(pointer as *mut u8);
+        let meta = Tree::meta(tree);
+        let Tree {
+            span, max_order, ..
+        } = tree.read();
+        let mut offset = pointer as usize - Tree::base(tree) as usize;
+        let mut order = *meta.add(offset >> self.min_order) as u32;
+        debug_assert!(order < FREE as u32, "block freed twice");
+        self.stats.allocated -= 1 << order;
+        self.stats.requested -= layout.size();
+        while order < max_order {
+            let buddy = offset ^ (1 << order);
+            if buddy + (1 << order) > span
+                || *meta.add(buddy >> self.min_order) != FREE | order as u8
+            {
+                break;
+            }
+            self.unlink(Tree::base(tree).add(buddy) as *mut FreeBlock, order);
+            // the upper half's byte no longer starts a block
+            *meta.add(offset.max(buddy) >> self.min_order) = 0;
+            offset = offset.min(buddy);
+            order += 1;
+        }
+        self.push(tree, offset, order);
+    }
+}
+
+impl Drop for BuddyResource<'_> {
+    fn drop(&mut self) {
+        // Safety: allocations don't outlive their resource
+        unsafe { self.release() }
+    }
+}
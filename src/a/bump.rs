@@ -0,0 +1,134 @@
+use core::{alloc::Layout, mem, ptr::NonNull};
+
+/// The word [`Bump::push`] records the previous top in.
+pub(crate) const HEADER: usize = mem::size_of::<usize>();
+
+/// # A cursor bumping through one contiguous buffer
+///
+/// The allocation logic shared by the arena style resources. Alignment is computed on the actual
+/// address, so any alignment works as long as the padding fits.
+#[derive(Debug)]
+pub(crate) struct Bump {
+    start: *mut u8,
+    capacity: usize,
+    used: usize,
+}
+
+impl Bump {
+    pub(crate) const fn empty() -> Self {
+        Self::new(core::ptr::null_mut(), 0)
+    }
+
+    pub(crate) const fn new(start: *mut u8, capacity: usize) -> Self {
+        Self::with_used(start, capacity, 0)
+    }
+
+    /// Picks a buffer back up with its first `used` bytes still handed out.
+    pub(crate) const fn with_used(start: *mut u8, capacity: usize, used: usize) -> Self {
+        Self {
+            start,
+            capacity,
+            used,
+        }
+    }
+
+    /// Bytes handed out so far, including alignment padding.
+    pub(crate) fn used(&self) -> usize {
+        self.used
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Forgets every allocation.
+    pub(crate) fn reset(&mut self) {
+        self.used = 0;
+    }
+
+    /// Forgets every allocation made since `used` bytes were handed out.
+    ///
+    /// # Panics
+    /// If fewer than `used` bytes are handed out.
+    pub(crate) fn rewind(&mut self, used: usize) {
+        assert!(used <= self.used, "rewound past the checkpoint");
+        self.used = used;
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.capacity - self.used
+    }
+
+    /// Where the next allocation would start before padding.
+    pub(crate) fn top(&self) -> *mut u8 {
+        self.start.wrapping_add(self.used)
+    }
+
+    pub(crate) fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        self.allocate_after(0, layout)
+    }
+
+    /// Allocates like [`allocate`](Self::allocate), and records the bytes used before in a word
+    /// just before the block, so that [`pop`](Self::pop) gives back its padding too.
+    pub(crate) fn push(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        let previous = self.used;
+        let block = self.allocate_after(HEADER, layout)?;
+        // Safety: the header lies between the old top and the block
+        unsafe { (block.as_ptr().sub(HEADER) as *mut usize).write_unaligned(previous) };
+        Some(block)
+    }
+
+    /// Frees the block of `size` bytes at `pointer` from [`push`](Self::push), if it is the most
+    /// recent one. Returns whether it was.
+    pub(crate) fn pop(&mut self, pointer: *const u8, size: usize) -> bool {
+        let offset = pointer as usize - self.start as usize;
+        if offset + size != self.used {
+            return false;
+        }
+        // Safety: the block came from `push`, which wrote the header
+        self.used = unsafe { (self.start.add(offset - HEADER) as *const usize).read_unaligned() };
+        true
+    }
+
+    /// Moves the end of the buffer, which must stay past every allocation.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        debug_assert!(capacity >= self.used, "capacity below the top");
+        self.capacity = capacity;
+    }
+
+    /// Allocates `layout` after the top, leaving at least `reserve` bytes before it.
+    fn allocate_after(&mut self, reserve: usize, layout: Layout) -> Option<NonNull<u8>> {
+        if self.start.is_null() {
+            return None;
+        }
+        let start = self.start as usize;
+        let top = (start + self.used).checked_add(reserve)?;
+        let aligned = top.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+        let end = aligned.checked_add(layout.size())?;
+        if end > start + self.capacity {
+            return None;
+        }
+        self.used = end - start;
+        // offsetting the start keeps the pointer's provenance
+        NonNull::new(self.start.wrapping_add(aligned - start))
+    }
+
+    /// Extends the most recent allocation at `pointer` from `old_size` to `new_size` bytes.
+    pub(crate) fn try_grow_last(
+        &mut self,
+        pointer: *const u8,
+        old_size: usize,
+        new_size: usize,
+    ) -> bool {
+        let end = pointer as usize + old_size;
+        if end != self.top() as usize {
+            return false;
+        }
+        let extra = new_size - old_size;
+        if extra > self.remaining() {
+            return false;
+        }
+        self.used += extra;
+        true
+    }
+}
@@ -0,0 +1,85 @@
+use super::{Allocator, SyncAllocator, SystemResource};
+use alloc::{rc::Rc, sync::Arc};
+use core::cell::RefCell;
+use std::sync::Mutex;
+
+/// The process-wide default, lazily set to the system resource.
+static DEFAULT_RESOURCE: Mutex<Option<SyncAllocator>> = Mutex::new(None);
+
+thread_local! {
+    /// This thread's override of the process-wide default.
+    static THREAD_DEFAULT_RESOURCE: RefCell<Option<Allocator>> = const { RefCell::new(None) };
+    /// The last process-wide default handed out on this thread and its wrapper, so that repeated
+    /// calls give equal allocators.
+    static WRAPPED_DEFAULT_RESOURCE: RefCell<Option<(SyncAllocator, Allocator)>> =
+        const { RefCell::new(None) };
+}
+
+/// The process-wide default resource, as set by [`set_default_resource`].
+pub fn get_global_default_resource() -> SyncAllocator {
+    DEFAULT_RESOURCE
+        .lock()
+        .expect("default resource lock poisoned")
+        .get_or_insert_with(|| SyncAllocator::new(Arc::new(SystemResource::default())))
+        .clone()
+}
+
+/// Replaces the process-wide default resource, returning the previous one.
+pub fn set_default_resource(alloc: SyncAllocator) -> SyncAllocator {
+    let mut default = DEFAULT_RESOURCE
+        .lock()
+        .expect("default resource lock poisoned");
+    default
+        .replace(alloc)
+        .unwrap_or_else(|| SyncAllocator::new(Arc::new(SystemResource::default())))
+}
+
+/// Replaces this thread's override of the default resource, returning the previous override.
+///
+/// With no override, the thread uses the process-wide default.
+pub fn set_thread_default_resource(alloc: Option<Allocator>) -> Option<Allocator> {
+    THREAD_DEFAULT_RESOURCE.with(|default| default.replace(alloc))
+}
+
+/// # The resource containers use when none is given
+///
+/// This thread's override if there is one, otherwise the process-wide default.
+pub fn get_default_resource() -> Allocator {
+    if let Some(alloc) = THREAD_DEFAULT_RESOURCE.with(|default| default.borrow().clone()) {
+        return alloc;
+    }
+    let global = get_global_default_resource();
+    WRAPPED_DEFAULT_RESOURCE.with(|wrapped| {
+        let mut wrapped = wrapped.borrow_mut();
+        match &*wrapped {
+            Some((sync, alloc)) if *sync == global => alloc.clone(),
+            _ => {
+                let alloc = Allocator::new(Rc::new(RefCell::new(global.clone())));
+                *wrapped = Some((global, alloc.clone()));
+                alloc
+            }
+        }
+    })
+}
+
+/// # A scoped default resource
+///
+/// Overrides this thread's default resource until dropped, then restores the previous one.
+/// Guards must be dropped in the reverse order they were made.
+pub struct DefaultResourceGuard {
+    previous: Option<Allocator>,
+}
+
+impl DefaultResourceGuard {
+    pub fn new(alloc: Allocator) -> Self {
+        Self {
+            previous: set_thread_default_resource(Some(alloc)),
+        }
+    }
+}
+
+impl Drop for DefaultResourceGuard {
+    fn drop(&mut self) {
+        set_thread_default_resource(self.previous.take());
+    }
+}
@@ -0,0 +1,131 @@
+use super::{AllocError, AllocResult, Allocator, MemoryResource, MonotonicBufferResource};
+use core::{
+    alloc::Layout,
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    ptr::{self, NonNull},
+};
+
+/// Links a value that needs dropping into the arena's drop list; it sits just before the value.
+struct DropHeader {
+    next: Option<NonNull<DropHeader>>,
+    drop: unsafe fn(NonNull<DropHeader>),
+}
+
+#[repr(C)]
+struct Entry<T> {
+    header: DropHeader,
+    value: T,
+}
+
+/// Drops the value of the `Entry<T>` starting at `header`.
+unsafe fn drop_entry<T>(header: NonNull<DropHeader>) {
+    ptr::drop_in_place(ptr::addr_of_mut!(
+        (*header.cast::<Entry<T>>().as_ptr()).value
+    ));
+}
+
+/// # An arena for values of any types, which drops them
+///
+/// Moves values into memory taken in chunks from the upstream allocator, through a
+/// [`MonotonicBufferResource`], and hands out references living as long as the arena. Values
+/// that need dropping are linked into a drop list kept in the arena itself, and
+/// [`reset`](Self::reset) or dropping the arena runs their destructors in reverse order of
+/// allocation before returning the chunks.
+///
+/// Values can borrow from anything outliving the arena, `'a`; the arena is invariant over it,
+/// so that nothing shorter lived can slip in and be seen by a destructor.
+pub struct DropArena<'a> {
+    memory: RefCell<MonotonicBufferResource<'static>>,
+    drops: Cell<Option<NonNull<DropHeader>>>,
+    _values: PhantomData<*mut &'a ()>,
+}
+
+impl<'a> DropArena<'a> {
+    pub fn new(upstream: Allocator) -> Self {
+        Self {
+            memory: RefCell::new(MonotonicBufferResource::new(upstream)),
+            drops: Cell::new(None),
+            _values: PhantomData,
+        }
+    }
+
+    /// A handle to the allocator the memory comes from.
+    pub fn upstream(&self) -> Allocator {
+        self.memory.borrow().upstream().clone()
+    }
+
+    /// Moves `value` into the arena.
+    ///
+    /// # Panics
+    /// If the upstream allocator is out of memory.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: 'a>(&self, value: T) -> &mut T {
+        self.try_alloc(value)
+            .unwrap_or_else(|(_, err)| panic!("{}", err))
+    }
+
+    /// Moves `value` into the arena, or hands it back with the reason it didn't fit.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc<T: 'a>(&self, value: T) -> Result<&mut T, (T, AllocError)> {
+        if !core::mem::needs_drop::<T>() {
+            return match self.allocate(Layout::new::<T>()) {
+                Ok(pointer) => {
+                    let pointer = pointer.cast::<T>().as_ptr();
+                    // Safety: the block fits a `T` and lives as long as the arena
+                    unsafe {
+                        pointer.write(value);
+                        Ok(&mut *pointer)
+                    }
+                }
+                Err(err) => Err((value, err)),
+            };
+        }
+        let entry = match self.allocate(Layout::new::<Entry<T>>()) {
+            Ok(pointer) => pointer.cast::<Entry<T>>().as_ptr(),
+            Err(err) => return Err((value, err)),
+        };
+        // Safety: the block fits an entry, which stays until the drop list runs it
+        unsafe {
+            entry.write(Entry {
+                header: DropHeader {
+                    next: self.drops.get(),
+                    drop: drop_entry::<T>,
+                },
+                value,
+            });
+            // the header comes first, and a pointer from the entry reaches the value as well
+            self.drops.set(NonNull::new(entry as *mut DropHeader));
+            Ok(&mut (*entry).value)
+        }
+    }
+
+    /// Drops every value, newest first, and returns the memory upstream.
+    pub fn reset(&mut self) {
+        self.run_drops();
+        // Safety: the values were dropped, and `&mut self` outlives every reference to them
+        unsafe { self.memory.get_mut().release() };
+    }
+
+    fn allocate(&self, layout: Layout) -> AllocResult<()> {
+        // Safety: the block is only released with the whole arena
+        unsafe { self.memory.borrow_mut().try_allocate(layout) }
+    }
+
+    fn run_drops(&mut self) {
+        // taken one at a time, so that a panicking destructor leaves the rest to the next run
+        while let Some(header) = self.drops.get() {
+            // Safety: the list only holds entries whose values are still alive
+            unsafe {
+                self.drops.set(header.as_ref().next);
+                (header.as_ref().drop)(header);
+            }
+        }
+    }
+}
+
+impl Drop for DropArena<'_> {
+    fn drop(&mut self) {
+        self.run_drops();
+    }
+}
@@ -0,0 +1,106 @@
+use core::{alloc::Layout, any, fmt};
+
+/// Why a memory resource refused an allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocErrorKind {
+    /// The resource has no space left for the request.
+    Exhausted,
+    /// The resource cannot provide the requested alignment.
+    Misaligned,
+    /// The resource never serves blocks of the requested size.
+    UnsupportedSize,
+}
+
+impl fmt::Display for AllocErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AllocErrorKind::Exhausted => "resource exhausted",
+            AllocErrorKind::Misaligned => "alignment not supported",
+            AllocErrorKind::UnsupportedSize => "size not supported",
+        })
+    }
+}
+
+/// # A failed allocation
+///
+/// Records the requested layout, the type name of the resource that refused it and why.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocError {
+    layout: Option<Layout>,
+    resource: &'static str,
+    kind: AllocErrorKind,
+}
+
+impl AllocError {
+    pub fn new(layout: Layout, resource: &'static str, kind: AllocErrorKind) -> Self {
+        Self {
+            layout: Some(layout),
+            resource,
+            kind,
+        }
+    }
+
+    /// `R` ran out of space for `layout`.
+    pub fn exhausted<R: ?Sized>(layout: Layout) -> Self {
+        Self::new(layout, any::type_name::<R>(), AllocErrorKind::Exhausted)
+    }
+
+    /// `R` cannot align a block to `layout.align()`.
+    pub fn misaligned<R: ?Sized>(layout: Layout) -> Self {
+        Self::new(layout, any::type_name::<R>(), AllocErrorKind::Misaligned)
+    }
+
+    /// `R` does not serve blocks of `layout.size()`.
+    pub fn unsupported_size<R: ?Sized>(layout: Layout) -> Self {
+        Self::new(
+            layout,
+            any::type_name::<R>(),
+            AllocErrorKind::UnsupportedSize,
+        )
+    }
+
+    /// The requested size overflowed before a [`Layout`] could even be built.
+    pub fn capacity_overflow<R: ?Sized>() -> Self {
+        Self {
+            layout: None,
+            resource: any::type_name::<R>(),
+            kind: AllocErrorKind::UnsupportedSize,
+        }
+    }
+
+    /// The requested layout, or `None` if computing it overflowed.
+    pub fn layout(&self) -> Option<Layout> {
+        self.layout
+    }
+
+    /// Type name of the resource that failed.
+    pub fn resource(&self) -> &'static str {
+        self.resource
+    }
+
+    pub fn kind(&self) -> AllocErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.layout {
+            Some(layout) => write!(
+                f,
+                "{} could not allocate {} bytes aligned to {}: {}",
+                self.resource,
+                layout.size(),
+                layout.align(),
+                self.kind
+            ),
+            None => write!(
+                f,
+                "{} could not allocate: requested size overflows",
+                self.resource
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AllocError {}
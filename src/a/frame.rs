@@ -0,0 +1,154 @@
+use super::{bump::Bump, AllocError, AllocResult, MemoryResource, Rewind};
+use core::{alloc::Layout, marker::PhantomData, mem::MaybeUninit};
+
+/// A position in a frame to [`rewind`](Rewind::rewind) to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameCheckpoint {
+    frame: u64,
+    used: usize,
+}
+
+/// # A double buffered arena for per frame temporaries
+///
+/// Splits a borrowed buffer into two bump arenas and allocates from one per frame.
+/// [`next_frame`](Self::next_frame) swaps them and resets the one it swaps to, so memory
+/// allocated in frame N stays valid through frame N+1 and is reclaimed in bulk when frame N+2
+/// begins. Deallocation does nothing.
+///
+/// Each frame can use half the buffer; beyond that allocations fail with
+/// [`AllocErrorKind::Exhausted`](super::AllocErrorKind::Exhausted). The bytes every frame used,
+/// its high-water mark, are reported as frames end, to size the buffer from real runs.
+#[derive(Debug)]
+pub struct FrameResource<'a> {
+    arenas: [Bump; 2],
+    /// The arena of the current frame.
+    current: usize,
+    frame: u64,
+    /// The most the current frame used before rewinding or resetting.
+    frame_high_water: usize,
+    /// The most any earlier frame used.
+    high_water: usize,
+    _buffer: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> FrameResource<'a> {
+    pub fn new(buffer: &'a mut [MaybeUninit<u8>]) -> Self {
+        let start = buffer.as_mut_ptr() as *mut u8;
+        let half = buffer.len() / 2;
+        Self {
+            arenas: [
+                Bump::new(start, half),
+                Bump::new(start.wrapping_add(half), buffer.len() - half),
+            ],
+            current: 0,
+            frame: 0,
+            frame_high_water: 0,
+            high_water: 0,
+            _buffer: PhantomData,
+        }
+    }
+
+    /// How many times [`next_frame`](Self::next_frame) has been called.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Bytes the current frame has used so far, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.arenas[self.current].used()
+    }
+
+    /// Bytes the previous frame used, which are still live.
+    pub fn previous_used(&self) -> usize {
+        self.arenas[self.current ^ 1].used()
+    }
+
+    /// Bytes the current frame has left; an aligned request may need some of them for padding.
+    pub fn remaining(&self) -> usize {
+        self.arenas[self.current].remaining()
+    }
+
+    /// The most bytes the current frame has used at once so far.
+    pub fn frame_high_water(&self) -> usize {
+        self.frame_high_water.max(self.used())
+    }
+
+    /// The most bytes any frame has used at once, the current one included.
+    pub fn high_water(&self) -> usize {
+        self.high_water.max(self.frame_high_water())
+    }
+
+    /// Ends the current frame and starts the next, reclaiming everything allocated the frame
+    /// before the one ending. Returns the ending frame's high-water mark.
+    ///
+    /// # Safety
+    /// Nothing allocated in the frame before the one ending may be used afterwards.
+    pub unsafe fn next_frame(&mut self) -> usize {
+        let mark = self.frame_high_water();
+        self.high_water = self.high_water.max(mark);
+        self.frame_high_water = 0;
+        self.current ^= 1;
+        self.arenas[self.current].reset();
+        self.frame += 1;
+        mark
+    }
+
+    /// Reclaims both frames, invalidating every allocation. The frame count and high-water marks
+    /// carry on.
+    ///
+    /// # Safety
+    /// No allocation of either frame may be used afterwards.
+    pub unsafe fn reset(&mut self) {
+        self.frame_high_water = self.frame_high_water();
+        self.arenas.iter_mut().for_each(Bump::reset);
+    }
+}
+
+impl Rewind for FrameResource<'_> {
+    type Checkpoint = FrameCheckpoint;
+
+    fn checkpoint(&self) -> FrameCheckpoint {
+        FrameCheckpoint {
+            frame: self.frame,
+            used: self.used(),
+        }
+    }
+
+    /// # Panics
+    /// Also if the checkpoint was taken in an earlier frame.
+    unsafe fn rewind(&mut self, checkpoint: FrameCheckpoint) {
+        assert_eq!(
+            checkpoint.frame, self.frame,
+            "checkpoint from another frame"
+        );
+        self.frame_high_water = self.frame_high_water();
+        self.arenas[self.current].rewind(checkpoint.used)
+    }
+}
+
+impl MemoryResource for FrameResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        self.arenas[self.current]
+            .allocate(layout)
+            .map(|pointer| pointer.cast())
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate(&mut self, _pointer: *mut (), _layout: Layout) {}
+
+    /// Grows the current frame's most recent allocation while the frame has room.
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        // a block of the previous frame may end right where an empty current arena starts
+        self.used() > 0
+            && self.arenas[self.current].try_grow_last(
+                pointer as *const u8,
+                old_layout.size(),
+                new_layout.size(),
+            )
+    }
+}
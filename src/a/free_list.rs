@@ -0,0 +1,372 @@
+use super::{AllocError, AllocResult, MemoryResource};
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+/// A block starts with a tag word holding its size and flags.
+const TAG: usize = mem::size_of::<usize>();
+/// Payloads are aligned to two words, so blocks start one word before that.
+const ALIGN: usize = 2 * TAG;
+/// A free block holds its tag, list links and a footer repeating its size.
+const MIN_BLOCK: usize = 4 * TAG;
+const FREE: usize = 1;
+/// Set when the block just before is free, so its footer is valid.
+const PREV_FREE: usize = 2;
+const FLAGS: usize = FREE | PREV_FREE;
+
+/// Where a [`FreeListResource`] places a request among the free blocks that fit it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    /// The lowest addressed block.
+    FirstFit,
+    /// The first block at or after where the last search ended, wrapping around.
+    NextFit,
+    /// The smallest block.
+    BestFit,
+}
+
+#[repr(C)]
+struct Block {
+    tag: usize,
+    // only valid while the block is free
+    next: *mut Block,
+    prev: *mut Block,
+}
+
+impl Block {
+    unsafe fn size(block: *mut Block) -> usize {
+        (*block).tag & !FLAGS
+    }
+
+    unsafe fn is_free(block: *mut Block) -> bool {
+        (*block).tag & FREE != 0
+    }
+
+    unsafe fn next_phys(block: *mut Block) -> *mut Block {
+        (block as *mut u8).add(Block::size(block)) as *mut Block
+    }
+
+    /// The block just before, which must be free.
+    unsafe fn prev_phys(block: *mut Block) -> *mut Block {
+        let size = *(block as *mut usize).sub(1);
+        (block as *mut u8).sub(size) as *mut Block
+    }
+
+    unsafe fn payload(block: *mut Block) -> *mut u8 {
+        (block as *mut u8).add(TAG)
+    }
+
+    /// Marks `block` free with `size` bytes, writing its footer.
+    unsafe fn set_free(block: *mut Block, size: usize) {
+        (*block).tag = size | FREE;
+        *((block as *mut u8).add(size - TAG) as *mut usize) = size;
+        (*Block::next_phys(block)).tag |= PREV_FREE;
+    }
+
+    /// Marks `block` used with `size` bytes, keeping its `PREV_FREE` flag.
+    unsafe fn set_used(block: *mut Block, size: usize) {
+        (*block).tag = size | (*block).tag & PREV_FREE;
+        (*Block::next_phys(block)).tag &= !PREV_FREE;
+    }
+}
+
+/// How a [`FreeListResource`]'s region is used, in payload bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FreeListStats {
+    pub free: usize,
+    pub used: usize,
+    pub free_blocks: usize,
+    pub used_blocks: usize,
+    pub largest_free: usize,
+}
+
+/// # A general purpose heap over one region
+///
+/// Keeps the free blocks of a region in one address ordered list and places each request by a
+/// selectable [`Placement`] policy, so policies can be compared on the same trace. Every block
+/// carries a one word boundary tag, and free blocks repeat their size at their end, so a freed
+/// block merges with free neighbours on both sides immediately.
+///
+/// Searching and freeing into the list take time linear in the number of free blocks; see
+/// [`TlsfResource`](super::TlsfResource) for constant time.
+pub struct FreeListResource<'a> {
+    policy: Placement,
+    /// The first block of the region, null if it holds none.
+    first: *mut Block,
+    head: *mut Block,
+    /// Where the next search starts under [`Placement::NextFit`].
+    rover: *mut Block,
+    _region: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> FreeListResource<'a> {
+    /// Manages `region` as one free block, closed by a sentinel tag.
+    pub fn new(region: &'a mut [MaybeUninit<u8>], policy: Placement) -> Self {
+        let mut resource = Self {
+            policy,
+            first: ptr::null_mut(),
+            head: ptr::null_mut(),
+            rover: ptr::null_mut(),
+            _region: PhantomData,
+        };
+        let start = region.as_mut_ptr() as *mut u8;
+        let offset = (start as usize).wrapping_add(TAG).wrapping_neg() & (ALIGN - 1);
+        let size = region.len().saturating_sub(offset + TAG) & !(ALIGN - 1);
+        if size < MIN_BLOCK {
+            return resource;
+        }
+        // Safety: the block and its sentinel lie within the region
+        unsafe {
+            let block = start.add(offset) as *mut Block;
+            block.expose_provenance();
+            (*block).tag = size;
+            (*Block::next_phys(block)).tag = 0;
+            Block::set_free(block, size);
+            resource.link(block, ptr::null_mut());
+            resource.first = block;
+        }
+        resource
+    }
+
+    pub fn policy(&self) -> Placement {
+        self.policy
+    }
+
+    /// Changes the policy for later requests; the blocks already placed stay put.
+    pub fn set_policy(&mut self, policy: Placement) {
+        self.policy = policy;
+    }
+
+    /// Walks every block of the region, taking time linear in their number.
+    pub fn stats(&self) -> FreeListStats {
+        let mut stats = FreeListStats::default();
+        let mut block = self.first;
+        // Safety: the region holds a chain of blocks up to its sentinel
+        unsafe {
+            while !block.is_null() && Block::size(block) != 0 {
+                let payload = Block::size(block) - TAG;
+                if Block::is_free(block) {
+                    stats.free += payload;
+                    stats.free_blocks += 1;
+                    stats.largest_free = stats.largest_free.max(payload);
+                } else {
+                    stats.used += payload;
+                    stats.used_blocks += 1;
+                }
+                block = Block::next_phys(block);
+            }
+        }
+        stats
+    }
+
+    /// Links `block` into the list after `after`, or first if `after` is null.
+    unsafe fn link(&mut self, block: *mut Block, after: *mut Block) {
+        let next = if after.is_null() {
+            mem::replace(&mut self.head, block)
+        } else {
+            mem::replace(&mut (*after).next, block)
+        };
+        (*block).prev = after;
+        (*block).next = next;
+        if !next.is_null() {
+            (*next).prev = block;
+        }
+    }
+
+    unsafe fn unlink(&mut self, block: *mut Block) {
+        let (prev, next) = ((*block).prev, (*block).next);
+        if prev.is_null() {
+            self.head = next;
+        } else {
+            (*prev).next = next;
+        }
+        if !next.is_null() {
+            (*next).prev = prev;
+        }
+        if self.rover == block {
+            self.rover = next;
+        }
+    }
+
+    /// Links a free block in address order.
+    unsafe fn insert(&mut self, block: *mut Block) {
+        let mut after = ptr::null_mut();
+        let mut next = self.head;
+        while !next.is_null() && next < block {
+            after = next;
+            next = (*next).next;
+        }
+        self.link(block, after);
+    }
+
+    /// The gap before `block`'s payload meets `align`, if `block` then holds `size` bytes. A
+    /// gap is large enough to stay behind as a free block.
+    unsafe fn fit(block: *mut Block, size: usize, align: usize) -> Option<usize> {
+        let mut gap = (Block::payload(block) as usize).wrapping_neg() & (align - 1);
+        while gap != 0 && gap < MIN_BLOCK {
+            gap += align;
+        }
+        Some(gap).filter(|gap| gap.saturating_add(size) <= Block::size(block))
+    }
+
+    /// A free block for `size` bytes aligned to `align`, and its gap, by the policy.
+    unsafe fn find(&mut self, size: usize, align: usize) -> Option<(*mut Block, usize)> {
+        match self.policy {
+            Placement::FirstFit => self.search(self.head, ptr::null_mut(), size, align),
+            Placement::NextFit => {
+                let rover = self.rover;
+                self.search(rover, ptr::null_mut(), size, align)
+                    .or_else(|| self.search(self.head, rover, size, align))
+            }
+            Placement::BestFit => {
+                let mut best: Option<(*mut Block, usize)> = None;
+                let mut block = self.head;
+                while !block.is_null() {
+                    if let Some(gap) = Self::fit(block, size, align) {
+                        if best.is_none_or(|(best, _)| Block::size(block) < Block::size(best)) {
+                            best = Some((block, gap));
+                            if Block::size(block) == size {
+                                break;
+                            }
+                        }
+                    }
+                    block = (*block).next;
+                }
+                best
+            }
+        }
+    }
+
+    /// The first block from `from` up to `to` that fits.
+    unsafe fn search(
+        &self,
+        from: *mut Block,
+        to: *mut Block,
+        size: usize,
+        align: usize,
+    ) -> Option<(*mut Block, usize)> {
+        let mut block = from;
+        while !block.is_null() && block != to {
+            if let Some(gap) = Self::fit(block, size, align) {
+                return Some((block, gap));
+            }
+            block = (*block).next;
+        }
+        None
+    }
+
+    /// Marks `block` used with `size` bytes, linking what it has beyond that after `after` as a
+    /// free block if it's big enough.
+    unsafe fn split(&mut self, block: *mut Block, size: usize, after: *mut Block) {
+        let rest_size = Block::size(block) - size;
+        if rest_size < MIN_BLOCK {
+            Block::set_used(block, Block::size(block));
+            return;
+        }
+        // the rest's tag isn't written yet, so `set_used` can't clear its flag; `set_free` writes
+        // it whole instead
+        (*block).tag = size | (*block).tag & PREV_FREE;
+        let rest = Block::next_phys(block);
+        Block::set_free(rest, rest_size);
+        self.link(rest, after);
+        self.rover = rest;
+    }
+
+    /// The block whose payload starts at `pointer`.
+    fn block_of(pointer: *mut ()) -> *mut Block {
+        // the tag precedes the payload the caller was given, so its address is turned back into
+        // a pointer with the region provenance `new` exposed
+        ptr::with_exposed_provenance_mut(pointer as usize - TAG)
+    }
+
+    /// The block size needed for `size` bytes of payload.
+    fn block_size(size: usize) -> Option<usize> {
+        let size = size.checked_add(TAG + ALIGN - 1)? & !(ALIGN - 1);
+        Some(size.max(MIN_BLOCK))
+    }
+}
+
+impl MemoryResource for FreeListResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        let size = Self::block_size(layout.size())
+            .ok_or_else(|| AllocError::unsupported_size::<Self>(layout))?;
+        let (mut block, gap) = self
+            .find(size, layout.align().max(ALIGN))
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))?;
+        let mut after = (*block).prev;
+        self.unlink(block);
+        if gap != 0 {
+            // the gap stays behind as a free block, in the same place in the list
+            let whole = Block::size(block);
+            let aligned = (block as *mut u8).add(gap) as *mut Block;
+            (*aligned).tag = whole - gap;
+            Block::set_free(block, gap);
+            self.link(block, after);
+            after = block;
+            block = aligned;
+        }
+        self.split(block, size, after);
+        Ok(NonNull::new_unchecked(
+            Block::payload(block) as *mut MaybeUninit<()>
+        ))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), _layout: Layout) {
+        let mut block = Self::block_of(pointer);
+        debug_assert!(!Block::is_free(block), "block freed twice");
+        let mut size = Block::size(block);
+        let next = Block::next_phys(block);
+        let mut linked = false;
+        if (*block).tag & PREV_FREE != 0 {
+            // the free block before keeps its place in the list
+            block = Block::prev_phys(block);
+            size += Block::size(block);
+            linked = true;
+        }
+        if Block::is_free(next) {
+            size += Block::size(next);
+            if linked {
+                self.unlink(next);
+            } else {
+                // takes the place of the block after, nothing free lying between them
+                let after = (*next).prev;
+                self.unlink(next);
+                self.link(block, after);
+                linked = true;
+            }
+        }
+        Block::set_free(block, size);
+        if !linked {
+            self.insert(block);
+        }
+    }
+
+    /// Takes room from the next block when it is free.
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        _old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        let block = Self::block_of(pointer);
+        let size = match Self::block_size(new_layout.size()) {
+            Some(size) => size,
+            None => return false,
+        };
+        if Block::size(block) >= size {
+            return true;
+        }
+        let next = Block::next_phys(block);
+        if !Block::is_free(next) || Block::size(block) + Block::size(next) < size {
+            return false;
+        }
+        let after = (*next).prev;
+        self.unlink(next);
+        (*block).tag += Block::size(next);
+        self.split(block, size, after);
+        true
+    }
+}
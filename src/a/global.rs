@@ -0,0 +1,244 @@
+use super::{copy_block, AllocError, AllocResult, MemoryResource, SyncMemoryResource};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    mem::MaybeUninit,
+    ptr::{self, NonNull},
+};
+use std::alloc::System;
+
+/// # A global allocator as a memory resource
+///
+/// Forwards to any [`GlobalAlloc`], including its `realloc` and `alloc_zeroed`; the equivalent of
+/// pmr's `new_delete_resource`. Zero sized requests never reach the allocator and get a dangling,
+/// well aligned pointer instead.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GlobalAllocResource<A>(A);
+
+/// The resource over the system allocator.
+pub type SystemResource = GlobalAllocResource<System>;
+
+impl<A> GlobalAllocResource<A> {
+    pub const fn new(alloc: A) -> Self {
+        Self(alloc)
+    }
+
+    pub fn get(&self) -> &A {
+        &self.0
+    }
+}
+
+fn dangling(layout: Layout) -> NonNull<MaybeUninit<()>> {
+    // Safety: alignments are never zero
+    unsafe { NonNull::new_unchecked(layout.align() as *mut MaybeUninit<()>) }
+}
+
+impl<A> GlobalAllocResource<A>
+where
+    A: GlobalAlloc,
+{
+    unsafe fn allocate_block(&self, layout: Layout, zeroed: bool) -> AllocResult<()> {
+        if layout.size() == 0 {
+            return Ok(dangling(layout));
+        }
+        let pointer = if zeroed {
+            self.0.alloc_zeroed(layout)
+        } else {
+            self.0.alloc(layout)
+        };
+        NonNull::new(pointer as *mut MaybeUninit<()>)
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate_block(&self, pointer: *mut (), layout: Layout) {
+        if layout.size() != 0 {
+            self.0.dealloc(pointer as *mut u8, layout)
+        }
+    }
+
+    /// Resizes a block to `new_size` bytes, keeping its alignment and contents up to the
+    /// smaller of the two sizes. On failure the old block is left untouched.
+    ///
+    /// # Safety
+    /// `pointer` must have been allocated by this resource with `layout`. On success it must
+    /// not be used anymore; the returned block is released with `layout.align()` and `new_size`.
+    pub unsafe fn reallocate(
+        &self,
+        pointer: *mut (),
+        layout: Layout,
+        new_size: usize,
+    ) -> AllocResult<()> {
+        let new_layout = Layout::from_size_align(new_size, layout.align())
+            .map_err(|_| AllocError::capacity_overflow::<Self>())?;
+        if layout.size() == 0 {
+            return self.allocate_block(new_layout, false);
+        }
+        if new_size == 0 {
+            self.deallocate_block(pointer, layout);
+            return Ok(dangling(new_layout));
+        }
+        NonNull::new(self.0.realloc(pointer as *mut u8, layout, new_size) as *mut MaybeUninit<()>)
+            .ok_or_else(|| AllocError::exhausted::<Self>(new_layout))
+    }
+
+    /// `reallocate`, unless the alignment changes, which `GlobalAlloc::realloc` can't do.
+    unsafe fn resize(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        if old_layout.align() == new_layout.align() {
+            return self.reallocate(pointer, old_layout, new_layout.size());
+        }
+        let new_pointer = self.allocate_block(new_layout, false)?;
+        copy_block(
+            pointer,
+            new_pointer,
+            old_layout.size().min(new_layout.size()),
+        );
+        self.deallocate_block(pointer, old_layout);
+        Ok(new_pointer)
+    }
+
+    unsafe fn grow_zeroed_block(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        let new_pointer = self.resize(pointer, old_layout, new_layout)?;
+        (new_pointer.as_ptr() as *mut u8)
+            .add(old_layout.size())
+            .write_bytes(0, new_layout.size() - old_layout.size());
+        Ok(new_pointer)
+    }
+}
+
+impl<A> MemoryResource for GlobalAllocResource<A>
+where
+    A: GlobalAlloc,
+{
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        self.allocate_block(layout, false)
+    }
+
+    unsafe fn allocate_zeroed(&mut self, layout: Layout) -> AllocResult<()> {
+        self.allocate_block(layout, true)
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        self.deallocate_block(pointer, layout)
+    }
+
+    unsafe fn grow(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.resize(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.grow_zeroed_block(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.resize(pointer, old_layout, new_layout)
+    }
+}
+
+/// Global allocators are already thread-safe, so sharing one needs no lock.
+impl<A> SyncMemoryResource for GlobalAllocResource<A>
+where
+    A: GlobalAlloc + Send + Sync,
+{
+    unsafe fn try_allocate(&self, layout: Layout) -> AllocResult<()> {
+        self.allocate_block(layout, false)
+    }
+
+    unsafe fn allocate_zeroed(&self, layout: Layout) -> AllocResult<()> {
+        self.allocate_block(layout, true)
+    }
+
+    unsafe fn deallocate(&self, pointer: *mut (), layout: Layout) {
+        self.deallocate_block(pointer, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.resize(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.grow_zeroed_block(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.resize(pointer, old_layout, new_layout)
+    }
+}
+
+/// # A memory resource as the process allocator
+///
+/// Implements [`GlobalAlloc`] over a [`SyncMemoryResource`], so that it can be installed with
+/// `#[global_allocator]`:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static GLOBAL: ResourceGlobalAlloc<Mutex<MyResource>> =
+///     ResourceGlobalAlloc::new(Mutex::new(MyResource::new()));
+/// ```
+///
+/// The resource must not allocate through the global allocator itself, since it would re-enter
+/// its own lock.
+pub struct ResourceGlobalAlloc<S>(S);
+
+impl<S> ResourceGlobalAlloc<S> {
+    pub const fn new(resource: S) -> Self {
+        Self(resource)
+    }
+
+    pub fn get(&self) -> &S {
+        &self.0
+    }
+}
+
+unsafe impl<S> GlobalAlloc for ResourceGlobalAlloc<S>
+where
+    S: SyncMemoryResource,
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0
+            .try_allocate(layout)
+            .map_or(ptr::null_mut(), |p| p.as_ptr() as *mut u8)
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        self.0.deallocate(pointer as *mut (), layout)
+    }
+}
@@ -0,0 +1,476 @@
+use alloc::rc::Rc;
+use core::{
+    alloc::Layout,
+    cell::{Ref, RefCell, RefMut},
+    mem::MaybeUninit,
+    ptr::{self, NonNull},
+};
+
+/// Allocation methods shared by the allocator handles, built on the handle's own `resource()`
+/// accessor.
+macro_rules! allocator_methods {
+    () => {
+        /// # Safety
+        /// See [`MemoryResource::allocate`].
+        pub unsafe fn allocate_layout(&self, layout: Layout) -> PtrUninit<()> {
+            self.try_allocate_layout(layout).ok()
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::try_allocate`].
+        pub unsafe fn try_allocate_layout(&self, layout: Layout) -> AllocResult<()> {
+            self.resource().try_allocate(layout)
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::allocate_zeroed`].
+        pub unsafe fn allocate_zeroed_layout(&self, layout: Layout) -> AllocResult<()> {
+            self.resource().allocate_zeroed(layout)
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::deallocate`].
+        pub unsafe fn deallocate_layout(&self, pointer: *mut (), layout: Layout) {
+            self.resource().deallocate(pointer, layout)
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::grow`].
+        pub unsafe fn grow_layout(
+            &self,
+            pointer: *mut (),
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> AllocResult<()> {
+            self.resource().grow(pointer, old_layout, new_layout)
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::grow_zeroed`].
+        pub unsafe fn grow_zeroed_layout(
+            &self,
+            pointer: *mut (),
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> AllocResult<()> {
+            self.resource().grow_zeroed(pointer, old_layout, new_layout)
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::shrink`].
+        pub unsafe fn shrink_layout(
+            &self,
+            pointer: *mut (),
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> AllocResult<()> {
+            self.resource().shrink(pointer, old_layout, new_layout)
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::try_grow_in_place`].
+        pub unsafe fn try_grow_layout_in_place(
+            &self,
+            pointer: *mut (),
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> bool {
+            self.resource()
+                .try_grow_in_place(pointer, old_layout, new_layout)
+        }
+
+        /// Allocates uninitialized space for `num_objects` values of `T`.
+        ///
+        /// Returns `None` if the total size overflows `isize::MAX` or the resource is out of
+        /// memory.
+        ///
+        /// # Safety
+        /// The memory must be released with `deallocate::<T>` using the same `num_objects`.
+        pub unsafe fn allocate<T>(&self, num_objects: usize) -> PtrUninit<T> {
+            self.allocate_layout(Layout::array::<T>(num_objects).ok()?)
+                .map(NonNull::cast::<MaybeUninit<T>>)
+        }
+
+        /// Like `allocate`, but reports why the allocation failed.
+        ///
+        /// # Safety
+        /// See `allocate`.
+        pub unsafe fn try_allocate<T>(&self, num_objects: usize) -> AllocResult<T> {
+            self.try_allocate_layout(Self::array_layout::<T>(num_objects)?)
+                .map(NonNull::cast::<MaybeUninit<T>>)
+        }
+
+        /// Allocates zeroed space for `num_objects` values of `T`.
+        ///
+        /// # Safety
+        /// See `allocate`. The memory is only a valid `T` if all zero bits are.
+        pub unsafe fn allocate_zeroed<T>(&self, num_objects: usize) -> AllocResult<T> {
+            self.allocate_zeroed_layout(Self::array_layout::<T>(num_objects)?)
+                .map(NonNull::cast::<MaybeUninit<T>>)
+        }
+
+        /// # Safety
+        /// `pointer` must come from `allocate::<T>(num_objects)` on an equal allocator.
+        pub unsafe fn deallocate<T>(&self, pointer: *mut T, num_objects: usize) {
+            self.deallocate_layout(pointer as *mut (), Self::allocated_layout::<T>(num_objects))
+        }
+
+        /// Grows the space at `pointer` from `old_len` to `new_len` values of `T`, moving it if
+        /// needed. The first `old_len` values are kept, the rest is uninitialized.
+        ///
+        /// # Safety
+        /// `pointer` must come from this allocator with `old_len` objects, and `new_len` must not
+        /// be smaller. On success only the returned pointer may be used, and is released with
+        /// `new_len` objects.
+        pub unsafe fn grow<T>(
+            &self,
+            pointer: *mut T,
+            old_len: usize,
+            new_len: usize,
+        ) -> AllocResult<T> {
+            self.grow_layout(
+                pointer as *mut (),
+                Self::allocated_layout::<T>(old_len),
+                Self::array_layout::<T>(new_len)?,
+            )
+            .map(NonNull::cast::<MaybeUninit<T>>)
+        }
+
+        /// Like `grow`, but the new space past `old_len` values is zeroed.
+        ///
+        /// # Safety
+        /// See `grow`.
+        pub unsafe fn grow_zeroed<T>(
+            &self,
+            pointer: *mut T,
+            old_len: usize,
+            new_len: usize,
+        ) -> AllocResult<T> {
+            self.grow_zeroed_layout(
+                pointer as *mut (),
+                Self::allocated_layout::<T>(old_len),
+                Self::array_layout::<T>(new_len)?,
+            )
+            .map(NonNull::cast::<MaybeUninit<T>>)
+        }
+
+        /// Shrinks the space at `pointer` from `old_len` to `new_len` values of `T`, keeping the
+        /// first `new_len` values.
+        ///
+        /// # Safety
+        /// See `grow`, with `new_len` no larger than `old_len`.
+        pub unsafe fn shrink<T>(
+            &self,
+            pointer: *mut T,
+            old_len: usize,
+            new_len: usize,
+        ) -> AllocResult<T> {
+            self.shrink_layout(
+                pointer as *mut (),
+                Self::allocated_layout::<T>(old_len),
+                Self::allocated_layout::<T>(new_len),
+            )
+            .map(NonNull::cast::<MaybeUninit<T>>)
+        }
+
+        /// Grows the space at `pointer` to `new_len` values of `T` without moving it, if the
+        /// resource can. On `false` nothing changed.
+        ///
+        /// # Safety
+        /// See `grow`; on `true` `pointer` is released with `new_len` objects.
+        pub unsafe fn try_grow_in_place<T>(
+            &self,
+            pointer: *mut T,
+            old_len: usize,
+            new_len: usize,
+        ) -> bool {
+            match Layout::array::<T>(new_len) {
+                Ok(new_layout) => self.try_grow_layout_in_place(
+                    pointer as *mut (),
+                    Self::allocated_layout::<T>(old_len),
+                    new_layout,
+                ),
+                Err(_) => false,
+            }
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::allocate_bytes`].
+        pub unsafe fn allocate_bytes(&self, size: usize, align: usize) -> PtrUninit<()> {
+            self.allocate_layout(Layout::from_size_align(size, align).ok()?)
+        }
+
+        /// # Safety
+        /// See [`MemoryResource::deallocate_bytes`].
+        pub unsafe fn deallocate_bytes(&self, pointer: *mut (), size: usize, align: usize) {
+            self.deallocate_layout(pointer, Layout::from_size_align_unchecked(size, align))
+        }
+
+        fn array_layout<T>(num_objects: usize) -> Result<Layout, AllocError> {
+            Layout::array::<T>(num_objects).map_err(|_| AllocError::capacity_overflow::<Self>())
+        }
+
+        /// The layout of a live array, which can't overflow since it was allocated.
+        fn allocated_layout<T>(num_objects: usize) -> Layout {
+            Layout::array::<T>(num_objects).expect("more objects than could have been allocated")
+        }
+    };
+}
+
+/// Copies a block's contents into a freshly allocated one, the fallback for resizing.
+///
+/// # Safety
+/// `pointer` and `new_pointer` must be valid for `size` bytes and not overlap.
+unsafe fn copy_block(pointer: *mut (), new_pointer: NonNull<MaybeUninit<()>>, size: usize) {
+    ptr::copy_nonoverlapping(pointer as *const u8, new_pointer.as_ptr() as *mut u8, size);
+}
+
+#[cfg(feature = "allocator-api2")]
+mod allocator_api;
+mod arena;
+mod bitmap;
+mod buddy;
+mod bump;
+mod default;
+mod drop_arena;
+mod error;
+mod frame;
+mod free_list;
+mod global;
+mod monotonic;
+mod null;
+mod pool;
+mod rewind;
+mod ring;
+mod slab;
+mod stack;
+mod sync;
+mod tlsf;
+
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api::AllocatorApiResource;
+pub use arena::{ArenaCheckpoint, ArenaResource, ArrayArenaResource};
+pub use bitmap::{Bitmap, BitmapResource};
+pub use buddy::{BuddyResource, BuddyStats};
+pub use default::{
+    get_default_resource, get_global_default_resource, set_default_resource,
+    set_thread_default_resource, DefaultResourceGuard,
+};
+pub use drop_arena::DropArena;
+pub use error::{AllocError, AllocErrorKind};
+pub use frame::{FrameCheckpoint, FrameResource};
+pub use free_list::{FreeListResource, FreeListStats, Placement};
+pub use global::{GlobalAllocResource, ResourceGlobalAlloc, SystemResource};
+pub use monotonic::{MonotonicBufferResource, MonotonicCheckpoint};
+pub use null::NullResource;
+pub use pool::{PoolOptions, SynchronizedPoolResource, UnsynchronizedPoolResource};
+pub use rewind::{ArenaScope, Rewind};
+pub use ring::RingResource;
+pub use slab::{SlabResource, SlabStats};
+pub use stack::{StackCheckpoint, StackEnd, StackResource};
+pub use sync::{SyncAllocator, SyncMemoryResource};
+pub use tlsf::{TlsfResource, TlsfStats};
+
+/// A container that allocates through a shared handle, [`Allocator`] unless stated otherwise.
+pub trait AllocatorAwareContainer<A = Allocator> {
+    fn allocator(&self) -> A;
+}
+
+pub type Ptr<T> = Option<NonNull<T>>;
+pub type PtrUninit<T> = Ptr<MaybeUninit<T>>;
+pub type AllocResult<T> = Result<NonNull<MaybeUninit<T>>, AllocError>;
+
+/// # A source of raw memory
+///
+/// Resources implement one of `try_allocate`, `allocate` or the older `allocate_bytes`, and one of
+/// `deallocate` or `deallocate_bytes`; the rest are defined in terms of those, so leaving a whole
+/// group unimplemented recurses forever.
+pub trait MemoryResource {
+    /// Allocates a block of memory fitting `layout`, explaining why on failure.
+    ///
+    /// The default reports every failure of `allocate` as [`AllocErrorKind::Exhausted`].
+    ///
+    /// # Safety
+    /// See [`MemoryResource::allocate`].
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        self.allocate(layout)
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))
+    }
+
+    /// Allocates a block of memory fitting `layout`, or `None` if the resource cannot.
+    ///
+    /// # Safety
+    /// The returned block must only be released through `deallocate` on this resource with
+    /// the same `layout`.
+    unsafe fn allocate(&mut self, layout: Layout) -> PtrUninit<()> {
+        self.allocate_bytes(layout.size(), layout.align())
+    }
+
+    /// Allocates a block of zeroed memory fitting `layout`.
+    ///
+    /// The default zeroes a `try_allocate` block; resources whose memory is known to be zero
+    /// already, like fresh pages or a freshly reset arena, can skip that.
+    ///
+    /// # Safety
+    /// See [`MemoryResource::allocate`].
+    unsafe fn allocate_zeroed(&mut self, layout: Layout) -> AllocResult<()> {
+        let pointer = self.try_allocate(layout)?;
+        (pointer.as_ptr() as *mut u8).write_bytes(0, layout.size());
+        Ok(pointer)
+    }
+
+    /// Releases a block of memory previously returned by `allocate`.
+    ///
+    /// # Safety
+    /// `pointer` must have been allocated by this resource with the same `layout`, and must
+    /// not be used afterwards.
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        self.deallocate_bytes(pointer, layout.size(), layout.align())
+    }
+
+    /// Shim over `allocate` for a raw `size`/`align` pair.
+    ///
+    /// Returns `None` if `align` is not a power of two or `size` overflows when rounded up to
+    /// `align`.
+    ///
+    /// # Safety
+    /// See [`MemoryResource::allocate`].
+    unsafe fn allocate_bytes(&mut self, size: usize, align: usize) -> PtrUninit<()> {
+        self.try_allocate(Layout::from_size_align(size, align).ok()?)
+            .ok()
+    }
+
+    /// Shim over `deallocate` for a raw `size`/`align` pair.
+    ///
+    /// # Safety
+    /// See [`MemoryResource::deallocate`]. `size` and `align` must be the values the block was
+    /// allocated with.
+    unsafe fn deallocate_bytes(&mut self, pointer: *mut (), size: usize, align: usize) {
+        self.deallocate(pointer, Layout::from_size_align_unchecked(size, align))
+    }
+
+    /// Extends the block at `pointer` to `new_layout` without moving it, if the resource can.
+    ///
+    /// Returns `false`, leaving the block untouched, by default. Bump style resources can answer
+    /// `true` for their most recent allocation.
+    ///
+    /// # Safety
+    /// `pointer` must have been allocated by this resource with `old_layout`, and `new_layout`
+    /// must not be smaller. On `true` the block is released with `new_layout`.
+    unsafe fn try_grow_in_place(
+        &mut self,
+        _pointer: *mut (),
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> bool {
+        false
+    }
+
+    /// Resizes the block at `pointer` to the larger `new_layout`, keeping its contents.
+    ///
+    /// Grows in place when possible, otherwise allocates a new block, copies and releases the old
+    /// one. On failure the old block is untouched.
+    ///
+    /// # Safety
+    /// See [`MemoryResource::try_grow_in_place`]. On success only the returned block may be used,
+    /// and is released with `new_layout`.
+    unsafe fn grow(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        debug_assert!(new_layout.size() >= old_layout.size());
+        if pointer as usize & (new_layout.align() - 1) == 0
+            && self.try_grow_in_place(pointer, old_layout, new_layout)
+        {
+            return Ok(NonNull::new_unchecked(pointer as *mut MaybeUninit<()>));
+        }
+        let new_pointer = self.try_allocate(new_layout)?;
+        copy_block(pointer, new_pointer, old_layout.size());
+        self.deallocate(pointer, old_layout);
+        Ok(new_pointer)
+    }
+
+    /// Like `grow`, but the bytes past `old_layout.size()` are zeroed.
+    ///
+    /// # Safety
+    /// See [`MemoryResource::grow`].
+    unsafe fn grow_zeroed(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        let new_pointer = self.grow(pointer, old_layout, new_layout)?;
+        (new_pointer.as_ptr() as *mut u8)
+            .add(old_layout.size())
+            .write_bytes(0, new_layout.size() - old_layout.size());
+        Ok(new_pointer)
+    }
+
+    /// Resizes the block at `pointer` to the smaller `new_layout`, keeping its leading contents.
+    ///
+    /// Allocates a new block, copies and releases the old one by default.
+    ///
+    /// # Safety
+    /// See [`MemoryResource::grow`], with `new_layout` no larger than `old_layout`.
+    unsafe fn shrink(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+        let new_pointer = self.try_allocate(new_layout)?;
+        copy_block(pointer, new_pointer, new_layout.size());
+        self.deallocate(pointer, old_layout);
+        Ok(new_pointer)
+    }
+
+    /// Whether memory allocated from `self` can be deallocated through `other`, and vice versa.
+    ///
+    /// Defaults to identity: a resource is only equal to itself.
+    fn is_equal(&self, other: &dyn MemoryResource) -> bool {
+        ptr::eq(
+            self as *const Self as *const (),
+            other as *const dyn MemoryResource as *const (),
+        )
+    }
+}
+
+/// # A pointer to a memory resource
+///
+/// The handle is reference counted and borrows the resource only for the length of each call,
+/// so clones can share one resource on a single thread. Use [`SyncAllocator`] to share a resource
+/// between threads.
+///
+/// Two allocators are equal when their resources are [equal](MemoryResource::is_equal), meaning
+/// either can free what the other allocated. There is no `Hash`, since distinct resources may
+/// compare equal.
+#[derive(Clone)]
+pub struct Allocator(Rc<RefCell<dyn MemoryResource>>);
+
+impl Allocator {
+    pub fn new(resource: Rc<RefCell<dyn MemoryResource>>) -> Self {
+        Self(resource)
+    }
+
+    allocator_methods!();
+
+    fn resource(&self) -> RefMut<'_, dyn MemoryResource> {
+        self.0.borrow_mut()
+    }
+
+    pub fn get(&self) -> Ref<'_, dyn MemoryResource> {
+        self.0.borrow()
+    }
+}
+
+impl PartialEq for Allocator {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || self.0.borrow().is_equal(&*other.0.borrow())
+    }
+}
+
+impl Eq for Allocator {}
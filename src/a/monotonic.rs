@@ -0,0 +1,227 @@
+use super::{bump::Bump, AllocError, AllocResult, Allocator, MemoryResource, Rewind};
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr::{self, NonNull},
+};
+
+/// Chunk size used when neither a buffer nor an initial size is given.
+const DEFAULT_CHUNK_SIZE: usize = 1024;
+/// Each chunk taken from upstream is this many times larger than the last.
+const GROWTH_FACTOR: usize = 2;
+
+/// Header at the start of every upstream chunk, linking it to the previous one.
+struct Chunk {
+    next: Option<NonNull<Chunk>>,
+    layout: Layout,
+}
+
+impl Chunk {
+    /// A cursor over the chunk's memory past its header, with `used` bytes handed out.
+    unsafe fn bump(chunk: NonNull<Chunk>, used: usize) -> Bump {
+        let header = Layout::new::<Chunk>().size();
+        let size = chunk.as_ref().layout.size();
+        Bump::with_used((chunk.as_ptr() as *mut u8).add(header), size - header, used)
+    }
+}
+
+/// A position in a [`MonotonicBufferResource`] to [`rewind`](Rewind::rewind) to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MonotonicCheckpoint {
+    /// The chunk allocations were coming from, `None` for the initial buffer.
+    chunk: Option<NonNull<Chunk>>,
+    used: usize,
+}
+
+/// # A resource that only ever grows
+///
+/// The equivalent of pmr's `monotonic_buffer_resource`: allocations bump through an optional
+/// initial buffer, then through chunks taken from the upstream allocator, each twice as large
+/// as the last. Deallocation does nothing; memory comes back all
+/// at once with [`release`](Self::release) or on drop.
+///
+/// [Rewinding](Rewind::rewind) keeps the chunks taken after the checkpoint and reuses them
+/// before going upstream again, so a loop that rewinds every iteration settles on a fixed set
+/// of chunks.
+///
+/// The most recent allocation can be grown in place while its chunk has room.
+pub struct MonotonicBufferResource<'a> {
+    buffer: *mut u8,
+    buffer_len: usize,
+    current: Bump,
+    chunks: Option<NonNull<Chunk>>,
+    /// Chunks rewound past, oldest first, to allocate from before taking new ones.
+    spare: Option<NonNull<Chunk>>,
+    initial_chunk_size: usize,
+    next_chunk_size: usize,
+    upstream: Allocator,
+    _buffer: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl MonotonicBufferResource<'static> {
+    pub fn new(upstream: Allocator) -> Self {
+        Self::with_initial_size(DEFAULT_CHUNK_SIZE, upstream)
+    }
+
+    /// The first chunk taken from `upstream` holds at least `initial_size` bytes.
+    pub fn with_initial_size(initial_size: usize, upstream: Allocator) -> Self {
+        Self {
+            buffer: ptr::null_mut(),
+            buffer_len: 0,
+            current: Bump::empty(),
+            chunks: None,
+            spare: None,
+            initial_chunk_size: initial_size.max(1),
+            next_chunk_size: initial_size.max(1),
+            upstream,
+            _buffer: PhantomData,
+        }
+    }
+}
+
+impl<'a> MonotonicBufferResource<'a> {
+    /// Allocates from `buffer` first, going to `upstream` once it is used up.
+    ///
+    /// An [`Allocator`] needs a `'static` resource, so unless the buffer is too, the resource is
+    /// used directly through [`MemoryResource`].
+    pub fn with_buffer(buffer: &'a mut [MaybeUninit<u8>], upstream: Allocator) -> Self {
+        let (start, len) = (buffer.as_mut_ptr() as *mut u8, buffer.len());
+        let chunk_size = len.saturating_mul(GROWTH_FACTOR).max(1);
+        Self {
+            buffer: start,
+            buffer_len: len,
+            current: Bump::new(start, len),
+            chunks: None,
+            spare: None,
+            initial_chunk_size: chunk_size,
+            next_chunk_size: chunk_size,
+            upstream,
+            _buffer: PhantomData,
+        }
+    }
+
+    pub fn upstream(&self) -> &Allocator {
+        &self.upstream
+    }
+
+    /// Returns every chunk to upstream and starts over from the initial buffer, invalidating all
+    /// allocations made so far.
+    ///
+    /// # Safety
+    /// No allocation made so far may be used afterwards.
+    pub unsafe fn release(&mut self) {
+        for list in [self.chunks.take(), self.spare.take()] {
+            let mut chunk = list;
+            while let Some(current) = chunk {
+                // every chunk was allocated from upstream with the layout in its header
+                let Chunk { next, layout } = current.as_ptr().read();
+                self.upstream
+                    .deallocate_layout(current.as_ptr() as *mut (), layout);
+                chunk = next;
+            }
+        }
+        self.current = Bump::new(self.buffer, self.buffer_len);
+        self.next_chunk_size = self.initial_chunk_size;
+    }
+
+    /// Allocates `layout` from the next spare chunk if it fits there, or from a new one.
+    unsafe fn allocate_chunk(&mut self, layout: Layout) -> AllocResult<()> {
+        if let Some(spare) = self.spare {
+            let mut bump = Chunk::bump(spare, 0);
+            if let Some(pointer) = bump.allocate(layout) {
+                self.spare = spare.as_ref().next;
+                (*spare.as_ptr()).next = self.chunks;
+                self.chunks = Some(spare);
+                self.current = bump;
+                return Ok(pointer.cast());
+            }
+        }
+        self.allocate_new_chunk(layout)
+    }
+
+    /// Takes a chunk from upstream that fits `layout` and allocates from it.
+    unsafe fn allocate_new_chunk(&mut self, layout: Layout) -> AllocResult<()> {
+        let header = Layout::new::<Chunk>();
+        let needed = (header.size() + layout.align() - 1)
+            .checked_add(layout.size())
+            .ok_or_else(AllocError::capacity_overflow::<Self>)?;
+        let size = needed.max(self.next_chunk_size);
+        let chunk_layout = Layout::from_size_align(size, header.align().max(layout.align()))
+            .map_err(|_| AllocError::capacity_overflow::<Self>())?;
+        let chunk = self.upstream.try_allocate_layout(chunk_layout)?.as_ptr() as *mut Chunk;
+        chunk.write(Chunk {
+            next: self.chunks,
+            layout: chunk_layout,
+        });
+        self.chunks = NonNull::new(chunk);
+        self.current = Bump::new((chunk as *mut u8).add(header.size()), size - header.size());
+        self.next_chunk_size = size.saturating_mul(GROWTH_FACTOR);
+        let pointer = self
+            .current
+            .allocate(layout)
+            .expect("the chunk was sized for the request");
+        Ok(pointer.cast())
+    }
+}
+
+impl Rewind for MonotonicBufferResource<'_> {
+    type Checkpoint = MonotonicCheckpoint;
+
+    fn checkpoint(&self) -> MonotonicCheckpoint {
+        MonotonicCheckpoint {
+            chunk: self.chunks,
+            used: self.current.used(),
+        }
+    }
+
+    /// Moves the chunks taken since the checkpoint to the spare ones, to be reused.
+    unsafe fn rewind(&mut self, checkpoint: MonotonicCheckpoint) {
+        if self.chunks == checkpoint.chunk {
+            return self.current.rewind(checkpoint.used);
+        }
+        let mut chunk = self.chunks;
+        while chunk != checkpoint.chunk {
+            let current = chunk.expect("rewound past the checkpoint");
+            chunk = current.as_ref().next;
+        }
+        while self.chunks != checkpoint.chunk {
+            let current = self.chunks.expect("the checkpoint's chunk is in the list");
+            self.chunks = current.as_ref().next;
+            (*current.as_ptr()).next = self.spare;
+            self.spare = Some(current);
+        }
+        self.current = match checkpoint.chunk {
+            Some(chunk) => Chunk::bump(chunk, checkpoint.used),
+            None => Bump::with_used(self.buffer, self.buffer_len, checkpoint.used),
+        };
+    }
+}
+
+impl MemoryResource for MonotonicBufferResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        match self.current.allocate(layout) {
+            Some(pointer) => Ok(pointer.cast()),
+            None => self.allocate_chunk(layout),
+        }
+    }
+
+    unsafe fn deallocate(&mut self, _pointer: *mut (), _layout: Layout) {}
+
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        self.current
+            .try_grow_last(pointer as *const u8, old_layout.size(), new_layout.size())
+    }
+}
+
+impl Drop for MonotonicBufferResource<'_> {
+    fn drop(&mut self) {
+        // Safety: allocations don't outlive their resource
+        unsafe { self.release() }
+    }
+}
@@ -0,0 +1,30 @@
+use super::{AllocError, AllocResult, MemoryResource, SyncMemoryResource};
+use core::alloc::Layout;
+
+/// # A resource that refuses every allocation
+///
+/// The equivalent of pmr's `null_memory_resource`: useful to check that containers cope with
+/// allocation failure, and as the last upstream of a chain of arenas. Since it never hands out
+/// memory, deallocating anything but a null pointer is a bug, asserted in debug builds.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullResource;
+
+impl MemoryResource for NullResource {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        Err(AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), _layout: Layout) {
+        debug_assert!(pointer.is_null(), "NullResource never allocates");
+    }
+}
+
+impl SyncMemoryResource for NullResource {
+    unsafe fn try_allocate(&self, layout: Layout) -> AllocResult<()> {
+        Err(AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate(&self, pointer: *mut (), _layout: Layout) {
+        debug_assert!(pointer.is_null(), "NullResource never allocates");
+    }
+}
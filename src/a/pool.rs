@@ -0,0 +1,375 @@
+use super::{
+    AllocError, AllocResult, Allocator, MemoryResource, SyncAllocator, SyncMemoryResource,
+};
+use alloc::vec::Vec;
+use core::{
+    alloc::Layout,
+    mem,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use std::{sync::Mutex, thread};
+
+/// The smallest block, big enough to hold the free list link.
+const MIN_BLOCK_SIZE: usize = mem::size_of::<FreeBlock>();
+/// The largest block that can be pooled, whatever the options ask for.
+const MAX_BLOCK_SIZE: usize = 1 << 20;
+/// Blocks in a pool's first chunk; each further chunk doubles, up to `max_blocks_per_chunk`.
+const INITIAL_BLOCKS_PER_CHUNK: usize = 8;
+
+/// # Tuning for the pool resources
+///
+/// The equivalent of pmr's `pool_options`. Zero means the default, and values outside what the
+/// pools support are clamped; [`UnsynchronizedPoolResource::options`] reports the values in use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolOptions {
+    /// The most blocks taken from upstream at once for any one pool.
+    pub max_blocks_per_chunk: usize,
+    /// Requests above this size, rounded up to a power of two, bypass the pools.
+    pub largest_required_pool_block: usize,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            max_blocks_per_chunk: 1024,
+            largest_required_pool_block: 4096,
+        }
+    }
+}
+
+impl PoolOptions {
+    fn normalized(self) -> Self {
+        let default = Self::default();
+        let or_default = |value, default| if value == 0 { default } else { value };
+        Self {
+            max_blocks_per_chunk: or_default(
+                self.max_blocks_per_chunk,
+                default.max_blocks_per_chunk,
+            ),
+            largest_required_pool_block: or_default(
+                self.largest_required_pool_block,
+                default.largest_required_pool_block,
+            )
+            .clamp(MIN_BLOCK_SIZE, MAX_BLOCK_SIZE)
+            .next_power_of_two(),
+        }
+    }
+
+    /// The pool serving `layout` once normalized, or `None` if it goes straight upstream.
+    fn class(&self, layout: Layout) -> Option<usize> {
+        let block_size = layout
+            .size()
+            .max(layout.align())
+            .max(MIN_BLOCK_SIZE)
+            .checked_next_power_of_two()?;
+        if block_size > self.largest_required_pool_block {
+            return None;
+        }
+        Some((block_size / MIN_BLOCK_SIZE).trailing_zeros() as usize)
+    }
+}
+
+struct FreeBlock {
+    next: Option<NonNull<FreeBlock>>,
+}
+
+/// Trailer at the end of every chunk, past its last block so that the blocks stay aligned.
+struct Chunk {
+    next: Option<NonNull<Chunk>>,
+    layout: Layout,
+}
+
+struct Pool {
+    block_size: usize,
+    free: Option<NonNull<FreeBlock>>,
+    chunks: Option<NonNull<Chunk>>,
+    next_blocks: usize,
+}
+
+/// # Size classed free lists
+///
+/// The bookkeeping shared by the pool resources, which supply the upstream. Every power of two
+/// from the smallest block up to the largest pooled one has a pool; its chunks are aligned to the
+/// block size, so each block is aligned to its own size.
+pub(crate) struct Pools {
+    pools: Vec<Pool>,
+    options: PoolOptions,
+}
+
+// Safety: the pools own their chunks and free blocks outright, nothing else points into them
+unsafe impl Send for Pools {}
+
+impl Pools {
+    pub(crate) fn new(options: PoolOptions) -> Self {
+        let options = options.normalized();
+        let classes = (options.largest_required_pool_block / MIN_BLOCK_SIZE).trailing_zeros() + 1;
+        let pools = (0..classes)
+            .map(|class| Pool {
+                block_size: MIN_BLOCK_SIZE << class,
+                free: None,
+                chunks: None,
+                next_blocks: INITIAL_BLOCKS_PER_CHUNK.min(options.max_blocks_per_chunk),
+            })
+            .collect();
+        Self { pools, options }
+    }
+
+    pub(crate) fn options(&self) -> PoolOptions {
+        self.options
+    }
+
+    /// The pool serving `layout`, or `None` if it goes straight upstream.
+    pub(crate) fn class(&self, layout: Layout) -> Option<usize> {
+        self.options.class(layout)
+    }
+
+    /// Takes a block from the pool, splitting a new chunk from `upstream` if it has none free.
+    /// `R` is the resource named in errors.
+    pub(crate) unsafe fn allocate<R>(
+        &mut self,
+        class: usize,
+        upstream: impl FnOnce(Layout) -> AllocResult<()>,
+    ) -> AllocResult<()> {
+        if let Some(block) = self.pop(class) {
+            return Ok(block.cast());
+        }
+        let chunk_layout = self.chunk_layout::<R>(class)?;
+        let chunk = upstream(chunk_layout)?;
+        self.add_chunk(class, chunk.cast(), chunk_layout);
+        Ok(self.pop(class).expect("a new chunk has free blocks").cast())
+    }
+
+    /// Takes a free block from the pool, if it has one.
+    unsafe fn pop(&mut self, class: usize) -> Option<NonNull<u8>> {
+        let pool = &mut self.pools[class];
+        let block = pool.free?;
+        pool.free = block.as_ptr().read().next;
+        Some(block.cast())
+    }
+
+    pub(crate) unsafe fn push(&mut self, class: usize, block: *mut ()) {
+        let pool = &mut self.pools[class];
+        let block = block as *mut FreeBlock;
+        block.write(FreeBlock { next: pool.free });
+        pool.free = NonNull::new(block);
+    }
+
+    /// The layout of the pool's next chunk.
+    fn chunk_layout<R>(&self, class: usize) -> Result<Layout, AllocError> {
+        let pool = &self.pools[class];
+        pool.block_size
+            .checked_mul(pool.next_blocks)
+            .and_then(|size| size.checked_add(mem::size_of::<Chunk>()))
+            .and_then(|size| Layout::from_size_align(size, pool.block_size).ok())
+            .ok_or_else(AllocError::capacity_overflow::<R>)
+    }
+
+    /// Splits a chunk allocated with `chunk_layout(class)` into free blocks.
+    unsafe fn add_chunk(&mut self, class: usize, chunk: NonNull<u8>, layout: Layout) {
+        let pool = &mut self.pools[class];
+        let (blocks, block_size) = (pool.next_blocks, pool.block_size);
+        let trailer = chunk.as_ptr().add(blocks * block_size) as *mut Chunk;
+        trailer.write(Chunk {
+            next: pool.chunks,
+            layout,
+        });
+        pool.chunks = NonNull::new(trailer);
+        pool.next_blocks = (blocks * 2).min(self.options.max_blocks_per_chunk);
+        for block in (0..blocks).rev() {
+            self.push(class, chunk.as_ptr().add(block * block_size) as *mut ());
+        }
+    }
+
+    /// Hands every chunk to `deallocate` and empties the pools.
+    pub(crate) unsafe fn release(&mut self, mut deallocate: impl FnMut(*mut (), Layout)) {
+        let initial_blocks = INITIAL_BLOCKS_PER_CHUNK.min(self.options.max_blocks_per_chunk);
+        for pool in &mut self.pools {
+            let mut chunk = pool.chunks.take();
+            while let Some(trailer) = chunk {
+                let Chunk { next, layout } = trailer.as_ptr().read();
+                let start =
+                    (trailer.as_ptr() as *mut u8).sub(layout.size() - mem::size_of::<Chunk>());
+                deallocate(start as *mut (), layout);
+                chunk = next;
+            }
+            pool.free = None;
+            pool.next_blocks = initial_blocks;
+        }
+    }
+}
+
+/// # A single threaded pool resource
+///
+/// The equivalent of pmr's `unsynchronized_pool_resource`: small requests are served from per
+/// size free lists, carved out of chunks taken from the upstream allocator that grow until
+/// [`PoolOptions::max_blocks_per_chunk`]. Freed blocks return to their pool, not upstream, until
+/// [`release`](Self::release) or drop. Requests larger than
+/// [`PoolOptions::largest_required_pool_block`] go straight to upstream.
+pub struct UnsynchronizedPoolResource {
+    pools: Pools,
+    upstream: Allocator,
+}
+
+impl UnsynchronizedPoolResource {
+    pub fn new(upstream: Allocator) -> Self {
+        Self::with_options(PoolOptions::default(), upstream)
+    }
+
+    pub fn with_options(options: PoolOptions, upstream: Allocator) -> Self {
+        Self {
+            pools: Pools::new(options),
+            upstream,
+        }
+    }
+
+    /// The options in use, after defaults and clamping.
+    pub fn options(&self) -> PoolOptions {
+        self.pools.options()
+    }
+
+    pub fn upstream(&self) -> &Allocator {
+        &self.upstream
+    }
+
+    /// Returns every chunk to upstream, invalidating all pooled allocations. Oversized ones
+    /// belong to upstream and are left alone.
+    ///
+    /// # Safety
+    /// No pooled allocation made so far may be used afterwards.
+    pub unsafe fn release(&mut self) {
+        let upstream = &self.upstream;
+        // the chunks were allocated from upstream with the layouts given here
+        self.pools
+            .release(|chunk, layout| upstream.deallocate_layout(chunk, layout))
+    }
+}
+
+impl MemoryResource for UnsynchronizedPoolResource {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        let upstream = &self.upstream;
+        match self.pools.class(layout) {
+            Some(class) => self
+                .pools
+                .allocate::<Self>(class, |chunk| upstream.try_allocate_layout(chunk)),
+            None => upstream.try_allocate_layout(layout),
+        }
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        match self.pools.class(layout) {
+            Some(class) => self.pools.push(class, pointer),
+            None => self.upstream.deallocate_layout(pointer, layout),
+        }
+    }
+}
+
+impl Drop for UnsynchronizedPoolResource {
+    fn drop(&mut self) {
+        // Safety: allocations don't outlive their resource
+        unsafe { self.release() }
+    }
+}
+
+/// Hands out shard indices, one per thread in turn.
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    static THREAD_SHARD: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// # A pool resource shared between threads
+///
+/// The counterpart of pmr's `synchronized_pool_resource`, behaving like
+/// [`UnsynchronizedPoolResource`] over an upstream [`SyncAllocator`]. The pools are split into
+/// shards, each behind its own lock, and every thread sticks to one of them, so threads only
+/// contend when they outnumber the shards. A block freed by another thread than the one that
+/// allocated it joins the freeing thread's shard, since blocks of one size are interchangeable.
+pub struct SynchronizedPoolResource {
+    options: PoolOptions,
+    shards: Vec<Mutex<Pools>>,
+    upstream: SyncAllocator,
+}
+
+impl SynchronizedPoolResource {
+    /// One shard per available core.
+    pub fn new(upstream: SyncAllocator) -> Self {
+        Self::with_options(PoolOptions::default(), upstream)
+    }
+
+    pub fn with_options(options: PoolOptions, upstream: SyncAllocator) -> Self {
+        let shards = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards(shards, options, upstream)
+    }
+
+    /// Splits the pools into `shards` independently locked shards, at least one.
+    pub fn with_shards(shards: usize, options: PoolOptions, upstream: SyncAllocator) -> Self {
+        Self {
+            options: options.normalized(),
+            shards: (0..shards.max(1))
+                .map(|_| Mutex::new(Pools::new(options)))
+                .collect(),
+            upstream,
+        }
+    }
+
+    /// The options in use, after defaults and clamping.
+    pub fn options(&self) -> PoolOptions {
+        self.options
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn upstream(&self) -> &SyncAllocator {
+        &self.upstream
+    }
+
+    /// Returns every chunk to upstream, invalidating all pooled allocations. Oversized ones
+    /// belong to upstream and are left alone.
+    ///
+    /// # Safety
+    /// No pooled allocation made so far, on any thread, may be used afterwards.
+    pub unsafe fn release(&mut self) {
+        let upstream = &self.upstream;
+        for shard in &mut self.shards {
+            let pools = shard.get_mut().expect("memory resource lock poisoned");
+            // the chunks were allocated from upstream with the layouts given here
+            pools.release(|chunk, layout| upstream.deallocate_layout(chunk, layout))
+        }
+    }
+
+    fn shard(&self) -> std::sync::MutexGuard<'_, Pools> {
+        let index = THREAD_SHARD.with(|shard| *shard) % self.shards.len();
+        self.shards[index]
+            .lock()
+            .expect("memory resource lock poisoned")
+    }
+}
+
+impl SyncMemoryResource for SynchronizedPoolResource {
+    unsafe fn try_allocate(&self, layout: Layout) -> AllocResult<()> {
+        let upstream = &self.upstream;
+        match self.options.class(layout) {
+            Some(class) => self
+                .shard()
+                .allocate::<Self>(class, |chunk| upstream.try_allocate_layout(chunk)),
+            None => upstream.try_allocate_layout(layout),
+        }
+    }
+
+    unsafe fn deallocate(&self, pointer: *mut (), layout: Layout) {
+        match self.options.class(layout) {
+            Some(class) => self.shard().push(class, pointer),
+            None => self.upstream.deallocate_layout(pointer, layout),
+        }
+    }
+}
+
+impl Drop for SynchronizedPoolResource {
+    fn drop(&mut self) {
+        // Safety: allocations don't outlive their resource
+        unsafe { self.release() }
+    }
+}
@@ -0,0 +1,82 @@
+use super::MemoryResource;
+use core::{cell::RefCell, fmt};
+
+/// # A resource that can free everything allocated after a point, at once
+///
+/// Implemented by the bump style resources, for which a [`checkpoint`](Self::checkpoint) is
+/// just the position of their top and [`rewind`](Self::rewind) puts it back without going
+/// through the individual allocations or any upstream allocator: in O(1), or in time linear in
+/// the chunks rewound past for a [`MonotonicBufferResource`](super::MonotonicBufferResource). Tentative work, like a
+/// parser's on a branch it may backtrack out of, allocates after a checkpoint and is thrown
+/// away by rewinding to it; [`ArenaScope`] does that on drop.
+pub trait Rewind: MemoryResource {
+    type Checkpoint: Copy + fmt::Debug;
+
+    /// Marks the current top of the resource.
+    fn checkpoint(&self) -> Self::Checkpoint;
+
+    /// Frees everything allocated since `checkpoint` was taken, invalidating it.
+    ///
+    /// # Safety
+    /// Nothing allocated since the checkpoint may be used afterwards, including memory still held
+    /// by a container.
+    ///
+    /// # Panics
+    /// If the resource has already been rewound or reset past the checkpoint.
+    unsafe fn rewind(&mut self, checkpoint: Self::Checkpoint);
+}
+
+/// # Rewinds a resource to where it was on creation, when dropped
+///
+/// Holds the resource by its [`RefCell`], the way an [`Allocator`](super::Allocator) shares it,
+/// so containers can keep allocating from it through their handles while the scope lives.
+/// Scopes nest; each has to be dropped before the ones outside it. [`keep`](Self::keep) ends a
+/// scope without rewinding, for work that turned out to be wanted.
+///
+/// Dropping the scope frees memory that containers may still point to, so creating one is
+/// unsafe: everything allocated in the scope has to be gone by the time it ends.
+pub struct ArenaScope<'r, R: Rewind> {
+    resource: &'r RefCell<R>,
+    checkpoint: R::Checkpoint,
+}
+
+impl<'r, R: Rewind> ArenaScope<'r, R> {
+    /// # Safety
+    /// Unless the scope is [kept](Self::keep), nothing allocated from the resource while it lives
+    /// may be used after it drops; containers allocating in it must be dropped first.
+    pub unsafe fn new(resource: &'r RefCell<R>) -> Self {
+        let checkpoint = resource.borrow().checkpoint();
+        Self {
+            resource,
+            checkpoint,
+        }
+    }
+
+    pub fn resource(&self) -> &'r RefCell<R> {
+        self.resource
+    }
+
+    pub fn checkpoint(&self) -> R::Checkpoint {
+        self.checkpoint
+    }
+
+    /// Ends the scope, keeping everything allocated in it.
+    pub fn keep(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl<R: Rewind> fmt::Debug for ArenaScope<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaScope")
+            .field("checkpoint", &self.checkpoint)
+            .finish()
+    }
+}
+
+impl<R: Rewind> Drop for ArenaScope<'_, R> {
+    fn drop(&mut self) {
+        // Safety: guaranteed by the caller of `new`
+        unsafe { self.resource.borrow_mut().rewind(self.checkpoint) };
+    }
+}
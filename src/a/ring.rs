@@ -0,0 +1,183 @@
+use super::{AllocError, AllocResult, MemoryResource};
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+/// Every record has a one word header just before its payload, holding the bytes from the header
+/// to the record's end; records and their sizes are multiples of it.
+const HEADER: usize = mem::size_of::<usize>();
+/// Marks a header's record as freed, waiting for the tail to reach it.
+const FREED: usize = 1;
+/// Words of alignment padding before a header read as zero, which no header is.
+const PADDING: usize = 0;
+
+/// # A circular buffer for allocations freed in about the order they were made
+///
+/// Allocations are carved from the head of a ring, and the tail follows behind them as they are
+/// freed. A block freed before older ones is only marked, and its space comes back once the
+/// tail reaches it, so a straggler holds up the space allocated after it. A request that doesn't
+/// fit before the end of the buffer wraps around to the start, leaving the rest of the buffer
+/// as filler until the tail passes.
+///
+/// Each block costs a one word header, and blocks are padded to a whole word. When a request
+/// fits in neither place it fails with
+/// [`AllocErrorKind::Exhausted`](super::AllocErrorKind::Exhausted), leaving live blocks as
+/// they are.
+#[derive(Debug)]
+pub struct RingResource<'a> {
+    start: *mut u8,
+    capacity: usize,
+    /// Offset where the next record starts.
+    head: usize,
+    /// Offset of the oldest record not yet reclaimed.
+    tail: usize,
+    /// Bytes between the tail and the head.
+    used: usize,
+    _buffer: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> RingResource<'a> {
+    pub fn new(buffer: &'a mut [MaybeUninit<u8>]) -> Self {
+        let start = buffer.as_mut_ptr() as *mut u8;
+        let offset = start.align_offset(HEADER).min(buffer.len());
+        start.expose_provenance();
+        Self {
+            start: start.wrapping_add(offset),
+            capacity: (buffer.len() - offset) & !(HEADER - 1),
+            head: 0,
+            tail: 0,
+            used: 0,
+            _buffer: PhantomData,
+        }
+    }
+
+    /// Bytes from the tail to the head, including headers, padding, filler and freed blocks the
+    /// tail hasn't reached yet.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.used == 0
+    }
+
+    /// Where a record for `layout` at `at` ends, and its header, if it ends by `limit`.
+    fn fit(&self, at: usize, limit: usize, layout: Layout) -> Option<(usize, usize)> {
+        let base = self.start as usize;
+        let after_header = (base + at).checked_add(HEADER)?;
+        let payload = after_header.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+        let end = payload
+            .checked_add(layout.size())?
+            .checked_add(HEADER - 1)?
+            & !(HEADER - 1);
+        Some((payload - HEADER - base, end - base)).filter(|&(_, end)| end <= limit)
+    }
+
+    unsafe fn word(&self, offset: usize) -> *mut usize {
+        self.start.add(offset) as *mut usize
+    }
+
+    /// Moves the tail past every padding word and freed record.
+    unsafe fn reclaim(&mut self) {
+        while self.used > 0 {
+            let size = match *self.word(self.tail) {
+                PADDING => HEADER,
+                header if header & FREED != 0 => header & !FREED,
+                _ => break,
+            };
+            self.used -= size;
+            self.tail += size;
+            if self.tail == self.capacity {
+                self.tail = 0;
+            }
+        }
+        if self.used == 0 {
+            // start over at the front, so the next requests don't wrap needlessly
+            self.head = 0;
+            self.tail = 0;
+        }
+    }
+}
+
+impl MemoryResource for RingResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        let fit = if self.used == self.capacity {
+            None
+        } else if self.head < self.tail {
+            self.fit(self.head, self.tail, layout)
+                .map(|fit| (self.head, fit))
+        } else {
+            self.fit(self.head, self.capacity, layout)
+                .map(|fit| (self.head, fit))
+                .or_else(|| self.fit(0, self.tail, layout).map(|fit| (0, fit)))
+        };
+        let (at, (header, end)) = fit.ok_or_else(|| AllocError::exhausted::<Self>(layout))?;
+        if at != self.head {
+            // the rest of the buffer becomes a freed filler record
+            *self.word(self.head) = (self.capacity - self.head) | FREED;
+            self.used += self.capacity - self.head;
+        }
+        let mut pad = at;
+        while pad < header {
+            *self.word(pad) = PADDING;
+            pad += HEADER;
+        }
+        *self.word(header) = end - header;
+        self.used += end - at;
+        self.head = if end == self.capacity { 0 } else { end };
+        Ok(NonNull::new_unchecked(
+            self.start.add(header + HEADER) as *mut MaybeUninit<()>
+        ))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), _layout: Layout) {
+        // the header sits before the block the caller holds, so it's reached through the
+        // buffer provenance `new` exposed
+        let header = ptr::with_exposed_provenance_mut::<usize>(pointer as usize - HEADER);
+        debug_assert!(*header & FREED == 0, "block freed twice");
+        *header |= FREED;
+        self.reclaim();
+    }
+
+    /// Grows the newest block while it doesn't reach the tail or the end of the buffer.
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        _old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        let header = pointer as usize - HEADER - self.start as usize;
+        let end = header + *self.word(header);
+        let new_end = match (header + HEADER)
+            .checked_add(new_layout.size())
+            .and_then(|end| end.checked_add(HEADER - 1))
+        {
+            Some(new_end) => new_end & !(HEADER - 1),
+            None => return false,
+        };
+        if new_end <= end {
+            return true;
+        }
+        // only the newest block has free space after it, unless the ring is full or it ends
+        // the buffer
+        let limit = if self.head > self.tail {
+            self.capacity
+        } else {
+            self.tail
+        };
+        if end != self.head || self.used == self.capacity || new_end > limit {
+            return false;
+        }
+        *self.word(header) = new_end - header;
+        self.used += new_end - end;
+        self.head = if new_end == self.capacity { 0 } else { new_end };
+        true
+    }
+}
@@ -0,0 +1,211 @@
+use super::{AllocError, AllocResult, Allocator, MemoryResource};
+use core::{
+    alloc::Layout,
+    mem,
+    ptr::{self, NonNull},
+};
+
+struct FreeBlock {
+    next: Option<NonNull<FreeBlock>>,
+}
+
+/// Header at the start of every slab.
+struct Slab {
+    next: Option<NonNull<Slab>>,
+    used: usize,
+}
+
+/// How full a [`SlabResource`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlabStats {
+    pub slabs: usize,
+    pub blocks_per_slab: usize,
+    /// Blocks handed out and not yet freed.
+    pub used_blocks: usize,
+}
+
+impl SlabStats {
+    pub fn free_blocks(&self) -> usize {
+        self.slabs * self.blocks_per_slab - self.used_blocks
+    }
+}
+
+/// # A resource for blocks of one size
+///
+/// Serves every request that fits its block layout in O(1), from an intrusive free list over
+/// slabs taken from the upstream allocator. Requests that are larger fail with
+/// [`AllocErrorKind::UnsupportedSize`](super::AllocErrorKind::UnsupportedSize), and ones more
+/// aligned with [`AllocErrorKind::Misaligned`](super::AllocErrorKind::Misaligned).
+///
+/// Slabs are a power of two in size and aligned to it, so a block finds its slab's header
+/// without a search; freed blocks stay with the resource until [`release`](Self::release) or
+/// drop.
+pub struct SlabResource {
+    block: Layout,
+    slab: Layout,
+    first_block: usize,
+    blocks_per_slab: usize,
+    free: Option<NonNull<FreeBlock>>,
+    slabs: Option<NonNull<Slab>>,
+    slab_count: usize,
+    upstream: Allocator,
+}
+
+impl SlabResource {
+    /// A resource for `block`, taking slabs of at least `blocks_per_slab` blocks from `upstream`.
+    ///
+    /// Blocks are widened to hold a pointer and padded to their alignment; slabs are rounded up
+    /// to a power of two, with the spare room used for further blocks.
+    ///
+    /// # Panics
+    /// If a slab's size overflows.
+    pub fn new(block: Layout, blocks_per_slab: usize, upstream: Allocator) -> Self {
+        let block = Layout::from_size_align(
+            block.size().max(mem::size_of::<FreeBlock>()),
+            block.align().max(mem::align_of::<FreeBlock>()),
+        )
+        .expect("widening a layout to a pointer keeps it valid")
+        .pad_to_align();
+        let first_block = Layout::new::<Slab>()
+            .align_to(block.align())
+            .expect("a block's alignment is valid")
+            .pad_to_align()
+            .size();
+        let slab_size = block
+            .size()
+            .checked_mul(blocks_per_slab.max(1))
+            .and_then(|size| size.checked_add(first_block))
+            .and_then(usize::checked_next_power_of_two)
+            .expect("slab size overflows");
+        let slab = Layout::from_size_align(slab_size, slab_size).expect("slab size overflows");
+        Self {
+            block,
+            slab,
+            first_block,
+            blocks_per_slab: (slab_size - first_block) / block.size(),
+            free: None,
+            slabs: None,
+            slab_count: 0,
+            upstream,
+        }
+    }
+
+    /// A resource for single values of `T`.
+    pub fn for_type<T>(blocks_per_slab: usize, upstream: Allocator) -> Self {
+        Self::new(Layout::new::<T>(), blocks_per_slab, upstream)
+    }
+
+    /// The layout of every block, after widening and padding.
+    pub fn block_layout(&self) -> Layout {
+        self.block
+    }
+
+    pub fn upstream(&self) -> &Allocator {
+        &self.upstream
+    }
+
+    pub fn stats(&self) -> SlabStats {
+        SlabStats {
+            slabs: self.slab_count,
+            blocks_per_slab: self.blocks_per_slab,
+            used_blocks: self.occupancy().sum(),
+        }
+    }
+
+    /// The blocks in use in each slab, newest slab first.
+    pub fn occupancy(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut slab = self.slabs;
+        core::iter::from_fn(move || {
+            // Safety: the list only holds live slabs
+            let header = unsafe { slab?.as_ptr().read() };
+            slab = header.next;
+            Some(header.used)
+        })
+    }
+
+    /// Returns every slab to upstream, invalidating all allocations.
+    ///
+    /// # Safety
+    /// No block handed out so far may be used afterwards.
+    pub unsafe fn release(&mut self) {
+        let mut slab = self.slabs.take();
+        while let Some(current) = slab {
+            // every slab was allocated from upstream with `self.slab`
+            slab = current.as_ptr().read().next;
+            self.upstream
+                .deallocate_layout(current.as_ptr() as *mut (), self.slab);
+        }
+        self.free = None;
+        self.slab_count = 0;
+    }
+
+    /// The header of the slab holding `block`.
+    fn slab_of(&self, block: *mut ()) -> *mut Slab {
+        // the caller's pointer only covers its block, so the header's address is turned back
+        // into the pointer `add_slab` exposed for the whole slab
+        ptr::with_exposed_provenance_mut(block as usize & !(self.slab.size() - 1))
+    }
+
+    unsafe fn add_slab(&mut self) -> Result<(), AllocError> {
+        let slab = self.upstream.try_allocate_layout(self.slab)?.as_ptr() as *mut Slab;
+        slab.expose_provenance();
+        slab.write(Slab {
+            next: self.slabs,
+            used: 0,
+        });
+        self.slabs = NonNull::new(slab);
+        self.slab_count += 1;
+        let first = (slab as *mut u8).add(self.first_block);
+        for block in (0..self.blocks_per_slab).rev() {
+            let block = first.add(block * self.block.size()) as *mut FreeBlock;
+            block.write(FreeBlock { next: self.free });
+            self.free = NonNull::new(block);
+        }
+        Ok(())
+    }
+}
+
+impl MemoryResource for SlabResource {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        if layout.align() > self.block.align() {
+            return Err(AllocError::misaligned::<Self>(layout));
+        }
+        if layout.size() > self.block.size() {
+            return Err(AllocError::unsupported_size::<Self>(layout));
+        }
+        if self.free.is_none() {
+            self.add_slab()?;
+        }
+        let block = self.free.expect("a new slab has free blocks");
+        self.free = block.as_ptr().read().next;
+        (*self.slab_of(block.as_ptr() as *mut ())).used += 1;
+        Ok(block.cast())
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), _layout: Layout) {
+        if pointer.is_null() {
+            return;
+        }
+        (*self.slab_of(pointer)).used -= 1;
+        let block = pointer as *mut FreeBlock;
+        block.write(FreeBlock { next: self.free });
+        self.free = NonNull::new(block);
+    }
+
+    /// Every block has the same size, so a request grows in place as long as it still fits.
+    unsafe fn try_grow_in_place(
+        &mut self,
+        _pointer: *mut (),
+        _old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        new_layout.size() <= self.block.size() && new_layout.align() <= self.block.align()
+    }
+}
+
+impl Drop for SlabResource {
+    fn drop(&mut self) {
+        // Safety: allocations don't outlive their resource
+        unsafe { self.release() }
+    }
+}
@@ -0,0 +1,181 @@
+use super::{
+    bump::{Bump, HEADER},
+    AllocError, AllocResult, MemoryResource, Rewind,
+};
+use core::{alloc::Layout, marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
+
+/// Which end of a [`StackResource`]'s buffer new allocations come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackEnd {
+    /// Upwards from the start of the buffer.
+    Low,
+    /// Downwards from the end of the buffer.
+    High,
+}
+
+/// A position of both ends of a stack to [`rewind`](Rewind::rewind) to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackCheckpoint {
+    low: usize,
+    high: usize,
+}
+
+/// # A LIFO allocator over a borrowed buffer
+///
+/// Allocates by bumping a top like [`ArenaResource`](super::ArenaResource), and takes the top
+/// back down when the most recent allocation is freed, so memory is reused as long as frees
+/// come in reverse order; each allocation keeps the previous top in a one word header, so its
+/// alignment padding comes back too. Freeing anything else is a bug, caught by a debug
+/// assertion; in release builds that block stays taken until [`reset`](Self::reset).
+///
+/// The buffer has two ends: allocations come from the low end by default, and from the high end
+/// after [`set_end`](Self::set_end), so long lived data and scratch data can share one buffer
+/// without interleaving. Frees find their end by address. Allocations fail with
+/// [`AllocErrorKind::Exhausted`](super::AllocErrorKind::Exhausted) once the ends meet.
+#[derive(Debug)]
+pub struct StackResource<'a> {
+    start: *mut u8,
+    capacity: usize,
+    /// The low end, whose capacity ends at the high end's bottom.
+    low: Bump,
+    /// Offset of the high end's bottom.
+    high: usize,
+    end: StackEnd,
+    _buffer: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> StackResource<'a> {
+    pub fn new(buffer: &'a mut [MaybeUninit<u8>]) -> Self {
+        let start = buffer.as_mut_ptr() as *mut u8;
+        Self {
+            start,
+            capacity: buffer.len(),
+            low: Bump::new(start, buffer.len()),
+            high: buffer.len(),
+            end: StackEnd::Low,
+            _buffer: PhantomData,
+        }
+    }
+
+    pub fn end(&self) -> StackEnd {
+        self.end
+    }
+
+    /// Takes later allocations from `end`; frees go to whichever end holds them.
+    pub fn set_end(&mut self, end: StackEnd) {
+        self.end = end;
+    }
+
+    /// Bytes held at the low end, including alignment padding.
+    pub fn used_low(&self) -> usize {
+        self.low.used()
+    }
+
+    /// Bytes held at the high end, including alignment padding.
+    pub fn used_high(&self) -> usize {
+        self.capacity - self.high
+    }
+
+    /// Bytes left between the ends; an aligned request may need some of them for padding.
+    pub fn remaining(&self) -> usize {
+        self.low.remaining()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Frees every allocation at both ends, invalidating them.
+    ///
+    /// # Safety
+    /// No allocation made so far, at either end, may be used afterwards.
+    pub unsafe fn reset(&mut self) {
+        self.low.reset();
+        self.set_high(self.capacity);
+    }
+
+    /// Takes `layout` from the high end, recording the old bottom in the word just after it.
+    ///
+    /// Mirrors [`Bump::push`] downwards, since a [`Bump`] only grows up.
+    fn allocate_high(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        let base = self.start as usize;
+        let below_header = (base + self.high).checked_sub(HEADER + layout.size())?;
+        let offset = (below_header & !(layout.align() - 1)).checked_sub(base)?;
+        if offset < self.low.used() {
+            return None;
+        }
+        // Safety: the header lies between the block and the old bottom
+        unsafe {
+            (self.start.add(offset + layout.size()) as *mut usize).write_unaligned(self.high)
+        };
+        self.set_high(offset);
+        // offsetting the start keeps the pointer's provenance
+        NonNull::new(self.start.wrapping_add(offset))
+    }
+
+    fn set_high(&mut self, high: usize) {
+        self.high = high;
+        self.low.set_capacity(high);
+    }
+}
+
+impl Rewind for StackResource<'_> {
+    type Checkpoint = StackCheckpoint;
+
+    fn checkpoint(&self) -> StackCheckpoint {
+        StackCheckpoint {
+            low: self.low.used(),
+            high: self.high,
+        }
+    }
+
+    /// Puts both ends back.
+    unsafe fn rewind(&mut self, checkpoint: StackCheckpoint) {
+        assert!(checkpoint.high >= self.high, "rewound past the checkpoint");
+        self.low.rewind(checkpoint.low);
+        self.set_high(checkpoint.high);
+    }
+}
+
+impl MemoryResource for StackResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        if layout.size() == 0 {
+            // nothing to free later, so neither end moves
+            let top = self.low.top();
+            return Ok(NonNull::new_unchecked(
+                top.wrapping_add(top.align_offset(layout.align())) as *mut MaybeUninit<()>,
+            ));
+        }
+        match self.end {
+            StackEnd::Low => self.low.push(layout),
+            StackEnd::High => self.allocate_high(layout),
+        }
+        .map(NonNull::cast)
+        .ok_or_else(|| AllocError::exhausted::<Self>(layout))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        if layout.size() == 0 || self.low.pop(pointer as *const u8, layout.size()) {
+            return;
+        }
+        let offset = pointer as usize - self.start as usize;
+        debug_assert_eq!(offset, self.high, "stack freed out of order");
+        if offset == self.high {
+            let bottom = (self.start.add(offset + layout.size()) as *const usize).read_unaligned();
+            self.set_high(bottom);
+        }
+    }
+
+    /// Grows the top allocation of the low end while the ends don't meet.
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        old_layout.size() != 0
+            && self
+                .low
+                .try_grow_last(pointer as *const u8, old_layout.size(), new_layout.size())
+    }
+}
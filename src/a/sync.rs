@@ -0,0 +1,245 @@
+use super::{copy_block, AllocError, AllocResult, MemoryResource, PtrUninit};
+use alloc::sync::Arc;
+use core::{alloc::Layout, mem::MaybeUninit, ptr, ptr::NonNull};
+use std::sync::Mutex;
+
+/// # A memory resource usable from many threads at once
+///
+/// Unlike [`MemoryResource`] every method takes `&self`, so the resource does its own
+/// synchronization. Any `Mutex<R>` over a sendable [`MemoryResource`] is one.
+pub trait SyncMemoryResource: Send + Sync {
+    /// # Safety
+    /// See [`MemoryResource::try_allocate`].
+    unsafe fn try_allocate(&self, layout: Layout) -> AllocResult<()>;
+
+    /// # Safety
+    /// See [`MemoryResource::allocate_zeroed`].
+    unsafe fn allocate_zeroed(&self, layout: Layout) -> AllocResult<()> {
+        let pointer = self.try_allocate(layout)?;
+        (pointer.as_ptr() as *mut u8).write_bytes(0, layout.size());
+        Ok(pointer)
+    }
+
+    /// # Safety
+    /// See [`MemoryResource::deallocate`].
+    unsafe fn deallocate(&self, pointer: *mut (), layout: Layout);
+
+    /// # Safety
+    /// See [`MemoryResource::try_grow_in_place`].
+    unsafe fn try_grow_in_place(
+        &self,
+        _pointer: *mut (),
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> bool {
+        false
+    }
+
+    /// # Safety
+    /// See [`MemoryResource::grow`].
+    unsafe fn grow(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        debug_assert!(new_layout.size() >= old_layout.size());
+        if pointer as usize & (new_layout.align() - 1) == 0
+            && self.try_grow_in_place(pointer, old_layout, new_layout)
+        {
+            return Ok(NonNull::new_unchecked(pointer as *mut MaybeUninit<()>));
+        }
+        let new_pointer = self.try_allocate(new_layout)?;
+        copy_block(pointer, new_pointer, old_layout.size());
+        self.deallocate(pointer, old_layout);
+        Ok(new_pointer)
+    }
+
+    /// # Safety
+    /// See [`MemoryResource::grow_zeroed`].
+    unsafe fn grow_zeroed(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        let new_pointer = self.grow(pointer, old_layout, new_layout)?;
+        (new_pointer.as_ptr() as *mut u8)
+            .add(old_layout.size())
+            .write_bytes(0, new_layout.size() - old_layout.size());
+        Ok(new_pointer)
+    }
+
+    /// # Safety
+    /// See [`MemoryResource::shrink`].
+    unsafe fn shrink(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+        let new_pointer = self.try_allocate(new_layout)?;
+        copy_block(pointer, new_pointer, new_layout.size());
+        self.deallocate(pointer, old_layout);
+        Ok(new_pointer)
+    }
+
+    /// See [`MemoryResource::is_equal`].
+    fn is_equal(&self, other: &dyn SyncMemoryResource) -> bool {
+        ptr::eq(
+            self as *const Self as *const (),
+            other as *const dyn SyncMemoryResource as *const (),
+        )
+    }
+}
+
+impl<R> SyncMemoryResource for Mutex<R>
+where
+    R: MemoryResource + Send,
+{
+    unsafe fn try_allocate(&self, layout: Layout) -> AllocResult<()> {
+        self.lock()
+            .expect("memory resource lock poisoned")
+            .try_allocate(layout)
+    }
+
+    unsafe fn allocate_zeroed(&self, layout: Layout) -> AllocResult<()> {
+        self.lock()
+            .expect("memory resource lock poisoned")
+            .allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&self, pointer: *mut (), layout: Layout) {
+        self.lock()
+            .expect("memory resource lock poisoned")
+            .deallocate(pointer, layout)
+    }
+
+    unsafe fn try_grow_in_place(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        self.lock()
+            .expect("memory resource lock poisoned")
+            .try_grow_in_place(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.lock()
+            .expect("memory resource lock poisoned")
+            .grow(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.lock()
+            .expect("memory resource lock poisoned")
+            .grow_zeroed(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.lock()
+            .expect("memory resource lock poisoned")
+            .shrink(pointer, old_layout, new_layout)
+    }
+}
+
+/// # A thread-safe pointer to a memory resource
+///
+/// The [`Allocator`](super::Allocator) counterpart backed by an `Arc`; it is `Send + Sync` and
+/// offers the same allocation methods.
+#[derive(Clone)]
+pub struct SyncAllocator(Arc<dyn SyncMemoryResource>);
+
+impl SyncAllocator {
+    pub fn new(resource: Arc<dyn SyncMemoryResource>) -> Self {
+        Self(resource)
+    }
+
+    allocator_methods!();
+
+    fn resource(&self) -> &dyn SyncMemoryResource {
+        &*self.0
+    }
+
+    pub fn get(&self) -> &dyn SyncMemoryResource {
+        &*self.0
+    }
+}
+
+impl PartialEq for SyncAllocator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0.is_equal(&*other.0)
+    }
+}
+
+impl Eq for SyncAllocator {}
+
+/// Lets a shared handle stand in as a resource, e.g. inside a single threaded
+/// [`Allocator`](super::Allocator).
+impl MemoryResource for SyncAllocator {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        self.0.try_allocate(layout)
+    }
+
+    unsafe fn allocate_zeroed(&mut self, layout: Layout) -> AllocResult<()> {
+        self.0.allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        self.0.deallocate(pointer, layout)
+    }
+
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        self.0.try_grow_in_place(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn grow(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.0.grow(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.0.grow_zeroed(pointer, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> AllocResult<()> {
+        self.0.shrink(pointer, old_layout, new_layout)
+    }
+}
@@ -0,0 +1,328 @@
+use super::{AllocError, AllocResult, MemoryResource};
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+/// Every block starts with a header, and blocks and payloads are aligned to its size.
+const HEADER: usize = mem::size_of::<[usize; 2]>();
+const ALIGN: usize = HEADER;
+/// A free block also holds its free list links.
+const MIN_BLOCK: usize = mem::size_of::<Block>();
+/// Second level classes per first level one.
+const SL_LOG: u32 = 4;
+const SL_COUNT: usize = 1 << SL_LOG;
+/// Sizes below this map linearly into the first first level class.
+const LINEAR: usize = SL_COUNT * ALIGN;
+const FL_COUNT: usize = usize::BITS as usize;
+/// The low bit of a block's size marks it free, sizes being multiples of `ALIGN`.
+const FREE: usize = 1;
+
+#[repr(C)]
+struct Block {
+    /// The block just before in memory, null for a pool's first block.
+    prev_phys: *mut Block,
+    size: usize,
+    // only valid while the block is free
+    next_free: *mut Block,
+    prev_free: *mut Block,
+}
+
+impl Block {
+    unsafe fn size(block: *mut Block) -> usize {
+        (*block).size & !FREE
+    }
+
+    unsafe fn is_free(block: *mut Block) -> bool {
+        (*block).size & FREE != 0
+    }
+
+    unsafe fn next_phys(block: *mut Block) -> *mut Block {
+        (block as *mut u8).add(Block::size(block)) as *mut Block
+    }
+
+    unsafe fn payload(block: *mut Block) -> *mut u8 {
+        (block as *mut u8).add(HEADER)
+    }
+
+    /// Sets the size of a used block, and makes the next block point back at it.
+    unsafe fn set_size(block: *mut Block, size: usize) {
+        (*block).size = size;
+        (*Block::next_phys(block)).prev_phys = block;
+    }
+}
+
+/// The free list of blocks of about `size` bytes.
+fn mapping(size: usize) -> (usize, usize) {
+    if size < LINEAR {
+        return (0, size / ALIGN);
+    }
+    let log = usize::BITS - 1 - size.leading_zeros();
+    let fl = (log - LINEAR.trailing_zeros() + 1) as usize;
+    let sl = (size >> (log - SL_LOG)) - SL_COUNT;
+    (fl, sl)
+}
+
+/// The free list whose blocks are all at least `size` bytes.
+fn mapping_search(size: usize) -> Option<(usize, usize)> {
+    if size < LINEAR {
+        return Some(mapping(size));
+    }
+    let log = usize::BITS - 1 - size.leading_zeros();
+    Some(mapping(size.checked_add((1 << (log - SL_LOG)) - 1)?))
+}
+
+/// How a [`TlsfResource`]'s pools are used, in payload bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TlsfStats {
+    pub free: usize,
+    pub used: usize,
+    pub free_blocks: usize,
+    pub used_blocks: usize,
+    pub largest_free: usize,
+}
+
+/// # A two level segregated fit allocator
+///
+/// Allocates and frees in constant time, for realtime code that can't afford a search: free
+/// blocks are kept in lists by size class, a power of two split into 16 linear steps, and two
+/// levels of bitmaps find a suitable non-empty list in a couple of bit scans. Freed blocks merge
+/// with free neighbours immediately through boundary tags, which bounds fragmentation.
+///
+/// The memory is one or more caller-provided pools; more can be added at any time with
+/// [`add_pool`](Self::add_pool). Each block costs a two word header.
+pub struct TlsfResource<'a> {
+    fl_bitmap: usize,
+    sl_bitmap: [usize; FL_COUNT],
+    free: [[*mut Block; SL_COUNT]; FL_COUNT],
+    /// The pools, each starting with a link to the next.
+    pools: *mut u8,
+    _pools: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+impl<'a> TlsfResource<'a> {
+    pub fn new(pool: &'a mut [MaybeUninit<u8>]) -> Self {
+        let mut resource = Self {
+            fl_bitmap: 0,
+            sl_bitmap: [0; FL_COUNT],
+            free: [[ptr::null_mut(); SL_COUNT]; FL_COUNT],
+            pools: ptr::null_mut(),
+            _pools: PhantomData,
+        };
+        resource.add_pool(pool);
+        resource
+    }
+
+    /// Adds `pool` as one more free block. Returns `false`, adding nothing, if the pool is too
+    /// small to hold a block besides its bookkeeping.
+    pub fn add_pool(&mut self, pool: &'a mut [MaybeUninit<u8>]) -> bool {
+        let start = pool.as_mut_ptr() as *mut u8;
+        let offset = start.align_offset(ALIGN);
+        let len = pool.len().saturating_sub(offset) & !(ALIGN - 1);
+        // a link to the next pool, a block and the sentinel closing the pool
+        if len < HEADER + MIN_BLOCK + HEADER {
+            return false;
+        }
+        // Safety: everything written lies within the pool
+        unsafe {
+            let link = start.add(offset);
+            link.expose_provenance();
+            (link as *mut *mut u8).write(self.pools);
+            self.pools = link;
+            let block = link.add(HEADER) as *mut Block;
+            let size = len - 2 * HEADER;
+            (*block).prev_phys = ptr::null_mut();
+            (*block).size = size;
+            let sentinel = Block::next_phys(block);
+            (*sentinel).prev_phys = block;
+            (*sentinel).size = 0;
+            self.insert(block);
+        }
+        true
+    }
+
+    /// Walks every block of every pool, taking time linear in their number.
+    pub fn stats(&self) -> TlsfStats {
+        let mut stats = TlsfStats::default();
+        let mut pool = self.pools;
+        while !pool.is_null() {
+            // Safety: pools hold a chain of blocks up to their sentinel
+            unsafe {
+                let mut block = pool.add(HEADER) as *mut Block;
+                while Block::size(block) != 0 {
+                    let payload = Block::size(block) - HEADER;
+                    if Block::is_free(block) {
+                        stats.free += payload;
+                        stats.free_blocks += 1;
+                        stats.largest_free = stats.largest_free.max(payload);
+                    } else {
+                        stats.used += payload;
+                        stats.used_blocks += 1;
+                    }
+                    block = Block::next_phys(block);
+                }
+                pool = *(pool as *mut *mut u8);
+            }
+        }
+        stats
+    }
+
+    unsafe fn insert(&mut self, block: *mut Block) {
+        let (fl, sl) = mapping(Block::size(block));
+        let head = self.free[fl][sl];
+        (*block).size |= FREE;
+        (*block).prev_free = ptr::null_mut();
+        (*block).next_free = head;
+        if !head.is_null() {
+            (*head).prev_free = block;
+        }
+        self.free[fl][sl] = block;
+        self.fl_bitmap |= 1 << fl;
+        self.sl_bitmap[fl] |= 1 << sl;
+    }
+
+    unsafe fn remove(&mut self, block: *mut Block) {
+        let (fl, sl) = mapping(Block::size(block));
+        let (prev, next) = ((*block).prev_free, (*block).next_free);
+        if prev.is_null() {
+            self.free[fl][sl] = next;
+            if next.is_null() {
+                self.sl_bitmap[fl] &= !(1 << sl);
+                if self.sl_bitmap[fl] == 0 {
+                    self.fl_bitmap &= !(1 << fl);
+                }
+            }
+        } else {
+            (*prev).next_free = next;
+        }
+        if !next.is_null() {
+            (*next).prev_free = prev;
+        }
+        (*block).size &= !FREE;
+    }
+
+    /// A free block of at least `size` bytes, with two bit scans.
+    fn find(&self, size: usize) -> Option<*mut Block> {
+        let (mut fl, sl) = mapping_search(size)?;
+        if fl >= FL_COUNT {
+            return None;
+        }
+        let mut sl_map = self.sl_bitmap[fl] & (!0 << sl);
+        if sl_map == 0 {
+            let fl_map = self.fl_bitmap & (!0usize).checked_shl(fl as u32 + 1).unwrap_or(0);
+            if fl_map == 0 {
+                return None;
+            }
+            fl = fl_map.trailing_zeros() as usize;
+            sl_map = self.sl_bitmap[fl];
+        }
+        Some(self.free[fl][sl_map.trailing_zeros() as usize])
+    }
+
+    /// Splits off what `block` has beyond `size` bytes as a free block, if it's big enough.
+    unsafe fn trim(&mut self, block: *mut Block, size: usize) {
+        let rest_size = Block::size(block) - size;
+        if rest_size < MIN_BLOCK {
+            return;
+        }
+        let rest = (block as *mut u8).add(size) as *mut Block;
+        (*rest).prev_phys = block;
+        Block::set_size(rest, rest_size);
+        Block::set_size(block, size);
+        self.insert(rest);
+    }
+
+    /// The block whose payload starts at `pointer`.
+    fn block_of(pointer: *mut ()) -> *mut Block {
+        // headers sit just before payloads, outside what the caller was handed, so the
+        // pointer is rebuilt from the pool provenance `add_pool` exposed
+        ptr::with_exposed_provenance_mut(pointer as usize - HEADER)
+    }
+
+    /// The block size needed for `size` bytes of payload.
+    fn block_size(size: usize) -> Option<usize> {
+        let payload = size.max(MIN_BLOCK - HEADER).checked_add(ALIGN - 1)? & !(ALIGN - 1);
+        payload.checked_add(HEADER)
+    }
+}
+
+impl MemoryResource for TlsfResource<'_> {
+    unsafe fn try_allocate(&mut self, layout: Layout) -> AllocResult<()> {
+        let align = layout.align();
+        let size = Self::block_size(layout.size())
+            .ok_or_else(|| AllocError::unsupported_size::<Self>(layout))?;
+        // room to move the payload up to its alignment, leaving a whole block in front
+        let search = if align <= ALIGN {
+            Some(size)
+        } else {
+            size.checked_add(align + MIN_BLOCK)
+        };
+        let mut block = search
+            .and_then(|search| self.find(search))
+            .ok_or_else(|| AllocError::exhausted::<Self>(layout))?;
+        self.remove(block);
+        if align > ALIGN {
+            let payload = Block::payload(block) as usize;
+            let mut gap = payload.wrapping_neg() & (align - 1);
+            if gap != 0 && gap < MIN_BLOCK {
+                gap += align;
+            }
+            if gap != 0 {
+                let aligned = (block as *mut u8).add(gap) as *mut Block;
+                (*aligned).prev_phys = block;
+                Block::set_size(aligned, Block::size(block) - gap);
+                Block::set_size(block, gap);
+                self.insert(block);
+                block = aligned;
+            }
+        }
+        self.trim(block, size);
+        Ok(NonNull::new_unchecked(
+            Block::payload(block) as *mut MaybeUninit<()>
+        ))
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), _layout: Layout) {
+        let mut block = Self::block_of(pointer);
+        debug_assert!(!Block::is_free(block), "block freed twice");
+        let prev = (*block).prev_phys;
+        if !prev.is_null() && Block::is_free(prev) {
+            self.remove(prev);
+            Block::set_size(prev, Block::size(prev) + Block::size(block));
+            block = prev;
+        }
+        let next = Block::next_phys(block);
+        if Block::is_free(next) {
+            self.remove(next);
+            Block::set_size(block, Block::size(block) + Block::size(next));
+        }
+        self.insert(block);
+    }
+
+    /// Takes room from the next block when it is free.
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        _old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        let block = Self::block_of(pointer);
+        let size = match Self::block_size(new_layout.size()) {
+            Some(size) => size,
+            None => return false,
+        };
+        if Block::size(block) >= size {
+            return true;
+        }
+        let next = Block::next_phys(block);
+        if !Block::is_free(next) || Block::size(block) + Block::size(next) < size {
+            return false;
+        }
+        self.remove(next);
+        Block::set_size(block, Block::size(block) + Block::size(next));
+        self.trim(block, size);
+        true
+    }
+}
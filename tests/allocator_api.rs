@@ -0,0 +1,58 @@
+use allocator_api2::{boxed::Box, vec::Vec};
+use hashbrown::HashMap;
+use yaap::a::{Allocator, AllocatorApiResource, SystemResource};
+
+mod tracked;
+use tracked::Tracked;
+
+#[test]
+fn vec_in_tracked() {
+    let res = Tracked::new(SystemResource::default());
+    {
+        let mut v = Vec::new_in(Allocator::new(res.clone()));
+        for i in 0..100usize {
+            v.push(i);
+        }
+        assert!(res.borrow().count() >= 100 * 8);
+        assert_eq!(v.iter().sum::<usize>(), 4950);
+        v.shrink_to_fit();
+        assert_eq!(res.borrow().count(), 100 * 8);
+    }
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn boxed_in_tracked() {
+    let res = Tracked::new(SystemResource::default());
+    let b = Box::new_in([7u32; 16], Allocator::new(res.clone()));
+    assert_eq!(res.borrow().count(), 64);
+    assert_eq!(b[15], 7);
+    drop(b);
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn hash_map_in_tracked() {
+    let res = Tracked::new(SystemResource::default());
+    {
+        let mut m = HashMap::new_in(Allocator::new(res.clone()));
+        for i in 0..32u32 {
+            m.insert(i, i * 2);
+        }
+        assert!(res.borrow().count() > 0);
+        assert_eq!(m[&31], 62);
+    }
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn resource_over_api_allocator() {
+    let res = Tracked::new(AllocatorApiResource::new(allocator_api2::alloc::Global));
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u64>(4).unwrap();
+        assert_eq!(res.borrow().count(), 32);
+        alloc.deallocate(p.as_ptr(), 4);
+    }
+    assert_eq!(res.borrow().count(), 0);
+}
@@ -0,0 +1,103 @@
+use core::{alloc::Layout, cell::RefCell, mem::MaybeUninit};
+use std::rc::Rc;
+use yaap::{
+    a::{self, Allocator, ArenaResource, ArrayArenaResource},
+    prelude::*,
+};
+
+mod fixtures;
+mod tracked;
+use fixtures::Buffer;
+use tracked::Tracked;
+
+#[test]
+fn slice_stats() {
+    let mut buffer = [MaybeUninit::<u8>::uninit(); 100];
+    let mut arena = ArenaResource::new(&mut buffer);
+    assert_eq!(
+        (arena.used(), arena.remaining(), arena.capacity()),
+        (0, 100, 100)
+    );
+    unsafe {
+        arena.try_allocate(Layout::new::<[u8; 30]>()).unwrap();
+    }
+    assert_eq!(
+        (arena.used(), arena.remaining(), arena.capacity()),
+        (30, 70, 100)
+    );
+    unsafe { arena.reset() };
+    assert_eq!(arena.used(), 0);
+}
+
+#[test]
+fn alignment_above_the_buffer() {
+    let mut buffer = [0u8; 8192];
+    let mut arena = ArenaResource::from(&mut buffer[..]);
+    unsafe {
+        arena.try_allocate(Layout::new::<u8>()).unwrap();
+        let layout = Layout::from_size_align(16, 4096).unwrap();
+        let p = arena.try_allocate(layout).unwrap().as_ptr() as usize;
+        assert_eq!(p % 4096, 0);
+        // the padding is used up too
+        assert!(arena.used() > 16 + 1);
+        assert_eq!(arena.used() + arena.remaining(), 8192);
+    }
+}
+
+#[test]
+fn padding_counts_against_capacity() {
+    let mut buffer = Buffer::<32>::new();
+    let mut arena = ArenaResource::new(&mut buffer.0);
+    let layout = Layout::from_size_align(17, 16).unwrap();
+    unsafe {
+        arena.try_allocate(Layout::new::<u8>()).unwrap();
+        // 15 bytes of padding would be needed: 1 + 15 + 17 > 32
+        let err = arena.try_allocate(layout).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert!(err.resource().contains("ArenaResource"));
+        assert_eq!(arena.used(), 1);
+        arena
+            .try_allocate(Layout::from_size_align(16, 16).unwrap())
+            .unwrap();
+        assert_eq!(arena.remaining(), 0);
+    }
+}
+
+#[test]
+fn exhausted() {
+    let mut buffer = [MaybeUninit::<u8>::uninit(); 16];
+    let mut arena = ArenaResource::new(&mut buffer);
+    unsafe {
+        arena.try_allocate(Layout::new::<[u8; 15]>()).unwrap();
+        let err = arena.try_allocate(Layout::new::<u16>()).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert_eq!(err.layout(), Some(Layout::new::<u16>()));
+        arena.try_allocate(Layout::new::<u8>()).unwrap();
+        assert_eq!(arena.remaining(), 0);
+    }
+}
+
+#[test]
+fn grow_last_in_place() {
+    let res = Tracked::new(ArrayArenaResource::<64>::new());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u8>(8).unwrap().as_ptr() as *mut u8;
+        assert!(alloc.try_grow_in_place(p, 8, 32));
+        assert_eq!(res.borrow().used(), 32);
+        assert!(!alloc.try_grow_in_place(p, 32, 65));
+        alloc.deallocate(p, 32);
+    }
+}
+
+#[test]
+fn from_array() {
+    let arena = Rc::new(RefCell::new(ArrayArenaResource::from_array([7; 8])));
+    let alloc = Allocator::new(arena.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u8>(8).unwrap().as_ptr() as *const u8;
+        assert_eq!(*p, 7);
+        assert!(alloc.try_allocate::<u8>(1).is_err());
+    }
+    assert_eq!(arena.borrow().remaining(), 0);
+}
@@ -0,0 +1,151 @@
+use core::{alloc::Layout, cell::RefCell, mem::MaybeUninit, ptr};
+use std::rc::Rc;
+use yaap::{
+    a::{self, Allocator, BitmapResource},
+    prelude::*,
+};
+
+mod fixtures;
+use fixtures::Buffer;
+
+#[test]
+fn bitmap_carved_from_region() {
+    let mut region = Buffer::<1024>::new();
+    let range = region.0.as_ptr_range();
+    let res = BitmapResource::new(&mut region.0, 16);
+    // 8 bytes of bitmap padded to a block, then 63 blocks
+    assert_eq!(res.block_count(), 63);
+    assert_eq!(res.block_size(), 16);
+    assert_eq!(res.used_blocks(), 0);
+    assert!(!res.owns(range.start as *const ()));
+    assert!(res.owns(range.start.wrapping_add(16) as *const ()));
+    assert!(res.owns(range.end.wrapping_sub(1) as *const ()));
+    assert!(!res.owns(range.end as *const ()));
+    assert!(!res.owns(ptr::null()));
+}
+
+#[test]
+fn runs_first_fit() {
+    let mut region = Buffer::<1024>::new();
+    let mut res = BitmapResource::new(&mut region.0, 16);
+    unsafe {
+        let a = res
+            .try_allocate(Layout::new::<[u8; 40]>())
+            .unwrap()
+            .as_ptr() as *mut ();
+        let b = res
+            .try_allocate(Layout::new::<[u8; 16]>())
+            .unwrap()
+            .as_ptr() as *mut ();
+        let c = res.try_allocate(Layout::new::<u8>()).unwrap().as_ptr() as *mut ();
+        assert_eq!(b as usize - a as usize, 48);
+        assert!(res.bitmap().to_string().starts_with("#####."));
+        assert_eq!(res.used_blocks(), 5);
+        res.deallocate(a, Layout::new::<[u8; 40]>());
+        assert!(res.bitmap().to_string().starts_with("...##."));
+        // a two block run fits in the gap, a four block one doesn't
+        let d = res
+            .try_allocate(Layout::new::<[u8; 32]>())
+            .unwrap()
+            .as_ptr() as *mut ();
+        assert_eq!(d, a);
+        let e = res
+            .try_allocate(Layout::new::<[u8; 64]>())
+            .unwrap()
+            .as_ptr() as *mut ();
+        assert_eq!(e as usize - c as usize, 16);
+        assert!(res.bitmap().to_string().starts_with("##.######."));
+        assert!(res.bitmap().is_used(0) && !res.bitmap().is_used(2));
+    }
+}
+
+#[test]
+fn alignment_above_block_size() {
+    let mut region = Buffer::<1024>::new();
+    let mut res = BitmapResource::new(&mut region.0, 16);
+    unsafe {
+        res.try_allocate(Layout::new::<u8>()).unwrap();
+        let layout = Layout::from_size_align(16, 128).unwrap();
+        for _ in 0..3 {
+            let p = res.try_allocate(layout).unwrap().as_ptr() as usize;
+            assert_eq!(p % 128, 0);
+        }
+    }
+}
+
+#[test]
+fn errors() {
+    let mut region = Buffer::<1024>::new();
+    let mut res = BitmapResource::new(&mut region.0, 16);
+    unsafe {
+        let err = res.try_allocate(Layout::new::<[u8; 1024]>()).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::UnsupportedSize);
+        assert!(err.resource().contains("BitmapResource"));
+        // every other block taken leaves no run of two
+        let blocks: Vec<_> = (0..63)
+            .map(|_| res.try_allocate(Layout::new::<u8>()).unwrap().as_ptr() as *mut ())
+            .collect();
+        for &p in blocks.iter().step_by(2) {
+            res.deallocate(p, Layout::new::<u8>());
+        }
+        let err = res.try_allocate(Layout::new::<[u8; 17]>()).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        res.try_allocate(Layout::new::<[u8; 16]>()).unwrap();
+    }
+}
+
+#[test]
+fn grow_in_place() {
+    let mut region = Buffer::<1024>::new();
+    let mut res = BitmapResource::new(&mut region.0, 16);
+    unsafe {
+        let p = res
+            .try_allocate(Layout::new::<[u8; 16]>())
+            .unwrap()
+            .as_ptr() as *mut ();
+        assert!(res.try_grow_in_place(p, Layout::new::<[u8; 16]>(), Layout::new::<[u8; 48]>()));
+        assert_eq!(res.used_blocks(), 3);
+        res.try_allocate(Layout::new::<u8>()).unwrap();
+        assert!(!res.try_grow_in_place(p, Layout::new::<[u8; 48]>(), Layout::new::<[u8; 64]>()));
+        res.deallocate(p, Layout::new::<[u8; 48]>());
+        assert_eq!(res.used_blocks(), 1);
+    }
+}
+
+#[test]
+fn display_wraps() {
+    let mut region = [MaybeUninit::uninit(); 4096];
+    let res = BitmapResource::new(&mut region, 8);
+    let text = res.bitmap().to_string();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(res.bitmap().len(), res.block_count());
+    assert!(lines[..lines.len() - 1].iter().all(|line| line.len() == 64));
+    assert_eq!(
+        text.chars().filter(|&c| c == '.').count(),
+        res.block_count()
+    );
+}
+
+#[test]
+fn empty_region() {
+    let mut res = BitmapResource::new(&mut [], 16);
+    assert_eq!(res.block_count(), 0);
+    assert!(res.bitmap().is_empty());
+    let err = unsafe { res.try_allocate(Layout::new::<u8>()) }.unwrap_err();
+    assert_eq!(err.kind(), a::AllocErrorKind::UnsupportedSize);
+}
+
+#[test]
+fn static_region() {
+    static mut REGION: Buffer<1024> = Buffer::new();
+    // Safety: the only reference to the region, taken once
+    let region = unsafe { &mut (*ptr::addr_of_mut!(REGION)).0 };
+    let res = Rc::new(RefCell::new(BitmapResource::new(region, 32)));
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u64>(10).unwrap().as_ptr();
+        assert_eq!(res.borrow().used_blocks(), 3);
+        alloc.deallocate(p, 10);
+    }
+    assert_eq!(res.borrow().used_blocks(), 0);
+}
@@ -0,0 +1,187 @@
+use core::{alloc::Layout, cell::RefCell, mem::MaybeUninit};
+use std::rc::Rc;
+use typenum::U16;
+use yaap::{
+    a::{self, Allocator, BuddyResource, SystemResource},
+    prelude::*,
+};
+
+mod deque;
+mod fixtures;
+mod tracked;
+use deque::Seque;
+use fixtures::{Lcg, PageBuffer};
+use tracked::Tracked;
+
+#[test]
+fn region_capacity() {
+    let mut region = PageBuffer::<4096>::new();
+    let res = BuddyResource::new(&mut region.0, 5);
+    let stats = res.stats();
+    assert_eq!(res.min_order(), 5);
+    // the header and a byte per block take the first few blocks
+    assert!(stats.capacity > 3800 && stats.capacity < 4096);
+    assert_eq!(stats.capacity % 32, 0);
+    assert_eq!(stats.largest_free, 2048);
+    assert_eq!((stats.allocated, stats.requested), (0, 0));
+}
+
+#[test]
+fn min_order_raised_to_hold_links() {
+    let mut region = PageBuffer::<4096>::new();
+    let res = BuddyResource::new(&mut region.0, 0);
+    assert_eq!(
+        res.min_order(),
+        (2 * core::mem::size_of::<usize>()).trailing_zeros()
+    );
+}
+
+#[test]
+fn split_and_coalesce() {
+    let mut region = PageBuffer::<4096>::new();
+    let mut res = BuddyResource::new(&mut region.0, 5);
+    let layout = Layout::from_size_align(2048, 1).unwrap();
+    unsafe {
+        let p = res.try_allocate(layout).unwrap().as_ptr() as *mut ();
+        assert_eq!(res.stats().largest_free, 1024);
+        let small = Layout::new::<[u8; 32]>();
+        let q = res.try_allocate(small).unwrap().as_ptr() as *mut ();
+        res.deallocate(p, layout);
+        assert_eq!(res.stats().largest_free, 2048);
+        res.deallocate(q, small);
+        assert_eq!(res.stats().largest_free, 2048);
+        assert_eq!(res.stats().allocated, 0);
+    }
+}
+
+#[test]
+fn coalesces_after_random_frees() {
+    let mut region = PageBuffer::<4096>::new();
+    let mut res = BuddyResource::new(&mut region.0, 5);
+    let initial = res.stats();
+    let sizes = [32, 64, 32, 128, 32, 256, 64];
+    unsafe {
+        let mut blocks = Vec::new();
+        for &size in sizes.iter().cycle() {
+            let layout = Layout::from_size_align(size, 1).unwrap();
+            match res.try_allocate(layout) {
+                Ok(p) => blocks.push((p.as_ptr() as *mut (), layout)),
+                Err(err) => {
+                    assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+                    break;
+                }
+            }
+        }
+        assert!(res.stats().free() < 256);
+        Lcg(7).shuffle(&mut blocks);
+        for (p, layout) in blocks {
+            res.deallocate(p, layout);
+        }
+    }
+    assert_eq!(res.stats(), initial);
+}
+
+#[test]
+fn internal_fragmentation() {
+    let mut region = PageBuffer::<4096>::new();
+    let mut res = BuddyResource::new(&mut region.0, 5);
+    unsafe {
+        res.try_allocate(Layout::new::<[u8; 33]>()).unwrap();
+        res.try_allocate(Layout::new::<[u8; 8]>()).unwrap();
+    }
+    let stats = res.stats();
+    assert_eq!((stats.allocated, stats.requested), (64 + 32, 33 + 8));
+    assert_eq!(stats.internal_fragmentation(), 31 + 24);
+}
+
+#[test]
+fn blocks_aligned_to_size() {
+    let mut region = PageBuffer::<4096>::new();
+    let mut res = BuddyResource::new(&mut region.0, 5);
+    unsafe {
+        for size in [40, 100, 300, 600, 32] {
+            let p = res
+                .try_allocate(Layout::from_size_align(size, 1).unwrap())
+                .unwrap()
+                .as_ptr() as usize;
+            assert_eq!(p % size.next_power_of_two(), 0);
+        }
+    }
+}
+
+#[test]
+fn region_errors() {
+    #[repr(C, align(64))]
+    struct Offset([MaybeUninit<u8>; 4096 + 32]);
+    let mut region = Offset([MaybeUninit::uninit(); 4096 + 32]);
+    // starts 32 bytes past a 64 byte boundary
+    let mut res = BuddyResource::new(&mut region.0[32..], 5);
+    unsafe {
+        let err = res
+            .try_allocate(Layout::from_size_align(64, 64).unwrap())
+            .unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Misaligned);
+        assert!(err.resource().contains("BuddyResource"));
+        let err = res.try_allocate(Layout::new::<[u8; 8192]>()).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::UnsupportedSize);
+        res.try_allocate(Layout::from_size_align(32, 32).unwrap())
+            .unwrap();
+    }
+    let mut res = BuddyResource::new(&mut [], 5);
+    let err = unsafe { res.try_allocate(Layout::new::<u8>()) }.unwrap_err();
+    assert_eq!(err.kind(), a::AllocErrorKind::UnsupportedSize);
+}
+
+#[test]
+fn upstream_chunks() {
+    let upstream = Tracked::new(SystemResource::default());
+    let res = Rc::new(RefCell::new(BuddyResource::with_upstream(
+        12,
+        5,
+        Allocator::new(upstream.clone()),
+    )));
+    let alloc = Allocator::new(res.clone());
+    assert_eq!(upstream.borrow().count(), 0);
+    unsafe {
+        let p = alloc.try_allocate::<u8>(1000).unwrap().as_ptr();
+        assert_eq!(upstream.borrow().count(), 4096);
+        assert_eq!(p as usize % 1024, 0);
+        for _ in 0..3 {
+            alloc.try_allocate::<u8>(1024).unwrap();
+        }
+        assert_eq!(upstream.borrow().count(), 2 * 4096);
+        let err = alloc.try_allocate::<u8>(4096).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::UnsupportedSize);
+        alloc.deallocate(p, 1000);
+    }
+    unsafe { res.borrow_mut().release() };
+    assert_eq!(upstream.borrow().count(), 0);
+    assert_eq!(res.borrow().stats(), Default::default());
+}
+
+#[test]
+#[should_panic(expected = "chunk too small for its metadata")]
+fn chunk_too_small() {
+    let upstream = Allocator::new(Rc::new(RefCell::new(SystemResource::default())));
+    BuddyResource::with_upstream(5, 5, upstream);
+}
+
+#[test]
+fn containers_reuse_the_chunk() {
+    let upstream = Tracked::new(SystemResource::default());
+    let res = Rc::new(RefCell::new(BuddyResource::with_upstream(
+        14,
+        4,
+        Allocator::new(upstream.clone()),
+    )));
+    for round in 0..3 {
+        let mut c = Seque::<usize, U16>::with_capacity_in(1, Allocator::new(res.clone()));
+        for i in 0..200 {
+            c.push_back(i + round);
+        }
+        assert!((0..200).all(|i| c[i] == i + round));
+    }
+    assert_eq!(res.borrow().stats().allocated, 0);
+    // freed memory is reused rather than taken again from upstream
+    assert_eq!(upstream.borrow().count(), 1 << 14);
+}
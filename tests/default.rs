@@ -0,0 +1,64 @@
+use std::thread;
+use typenum::U16;
+use yaap::{
+    a::{self, Allocator, DefaultResourceGuard, SystemResource},
+    prelude::*,
+};
+
+mod deque;
+mod tracked;
+use deque::Seque;
+use tracked::Tracked;
+
+#[test]
+fn default_works() {
+    let mut c = Seque::<usize, U16>::new();
+    for i in 0..40 {
+        c.push_back(i);
+    }
+    assert_eq!(c.iter().sum::<usize>(), 780);
+}
+
+#[test]
+fn default_is_stable() {
+    assert!(a::get_default_resource() == a::get_default_resource());
+}
+
+#[test]
+fn guard_overrides_and_restores() {
+    let res = Tracked::new(SystemResource::default());
+    let before = a::get_default_resource();
+    {
+        let _guard = DefaultResourceGuard::new(Allocator::new(res.clone()));
+        let mut c = Seque::<usize, U16>::new();
+        c.push_back(1);
+        assert!(res.borrow().count() > 0);
+        assert!(c.allocator() != before);
+    }
+    assert_eq!(res.borrow().count(), 0);
+    assert!(a::get_default_resource() == before);
+    let _c = Seque::<usize, U16>::new();
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn nested_guards() {
+    let outer = Allocator::new(Tracked::new(SystemResource::default()));
+    let inner = Allocator::new(Tracked::new(SystemResource::default()));
+    let _o = DefaultResourceGuard::new(outer.clone());
+    {
+        let _i = DefaultResourceGuard::new(inner.clone());
+        assert!(a::get_default_resource() == inner);
+    }
+    assert!(a::get_default_resource() == outer);
+}
+
+#[test]
+fn guard_is_per_thread() {
+    let local = Allocator::new(Tracked::new(SystemResource::default()));
+    let _guard = DefaultResourceGuard::new(local.clone());
+    let global = a::get_global_default_resource();
+    let other = thread::spawn(move || a::get_global_default_resource() == global);
+    assert!(other.join().unwrap());
+    assert!(a::get_default_resource() == local);
+}
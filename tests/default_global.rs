@@ -0,0 +1,26 @@
+// Kept apart from tests/default.rs, since swapping the process-wide default would race with tests
+// relying on it.
+use std::thread;
+use typenum::U16;
+use yaap::a::{self, SyncAllocator, SystemResource};
+
+mod deque;
+mod tracked;
+use deque::Seque;
+use tracked::Tracked;
+
+#[test]
+fn set_global_default() {
+    let res = Tracked::new_sync(SystemResource::default());
+    let tracked = SyncAllocator::new(res.clone());
+    let previous = a::set_default_resource(tracked.clone());
+    let count = thread::spawn(|| {
+        let mut c = Seque::<u64, U16>::new();
+        c.push_back(3);
+        c.iter().sum::<u64>()
+    });
+    assert_eq!(count.join().unwrap(), 3);
+    assert!(a::get_global_default_resource() == tracked);
+    assert!(a::set_default_resource(previous) == tracked);
+    assert_eq!(res.lock().unwrap().count(), 0);
+}
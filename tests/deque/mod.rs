@@ -1,11 +1,12 @@
 use core::{
     marker::PhantomData,
+    mem::MaybeUninit,
     ops,
     ptr::{drop_in_place, NonNull},
     slice::{self, Iter, IterMut},
 };
 use typenum::{IsLess, True, Unsigned, U255};
-use yaap::a::{self, Allocator};
+use yaap::a::{self, AllocError, Allocator};
 
 mod private {
     pub trait Sealed {}
@@ -35,48 +36,75 @@ where
     /// assert!(N::USIZE * size<T> < isize::MAX);
     const ARRAY_LEN: usize = N::USIZE;
 
-    pub fn with_data(alloc: &Allocator) -> Self {
-        Self {
-            data: Self::allocate_node_data(alloc),
+    pub fn try_with_data(alloc: &Allocator) -> Result<Self, AllocError> {
+        Ok(Self {
+            data: Some(Self::try_allocate_node_data(alloc)?),
             next: None,
             _p: PhantomData,
-        }
+        })
     }
 
-    fn allocate_node_data(alloc: &Allocator) -> Data<T> {
+    fn try_allocate_node_data(alloc: &Allocator) -> Result<NonNull<T>, AllocError> {
         // SAFETY: data is never accessed before first writing a valid value
-        unsafe { alloc.allocate::<T>(Self::ARRAY_LEN).map(NonNull::cast) }
+        unsafe { alloc.try_allocate::<T>(Self::ARRAY_LEN).map(NonNull::cast) }
     }
 
-    fn allocate_next_node(alloc: &Allocator) -> Link<T, N> {
+    fn try_allocate_next_node(alloc: &Allocator) -> Result<NonNull<Self>, AllocError> {
         // Safety: Able to remove MaybeUninit because underlying data is initialized
         unsafe {
-            alloc.allocate::<Node<T, N>>(1).map(|mut node| {
-                node.as_mut().as_mut_ptr().write(Self::with_data(alloc));
-                node.cast()
-            })
+            let node = alloc.try_allocate::<Node<T, N>>(1)?;
+            match Self::try_with_data(alloc) {
+                Ok(data) => {
+                    node.as_ptr().write(MaybeUninit::new(data));
+                    Ok(node.cast())
+                }
+                Err(e) => {
+                    alloc.deallocate(node.as_ptr(), 1);
+                    Err(e)
+                }
+            }
         }
     }
 
-    pub fn allocate_node_chain(&mut self, amount: usize, alloc: &Allocator) {
+    /// Appends `amount` empty nodes, or none if any allocation fails
+    pub fn try_allocate_node_chain(
+        &mut self,
+        amount: usize,
+        alloc: &Allocator,
+    ) -> Result<(), AllocError> {
+        let mut chain: Link<T, N> = None;
+        for _i in 0..amount {
+            match Self::try_allocate_next_node(alloc) {
+                Ok(mut node) => {
+                    unsafe { node.as_mut().next = chain };
+                    chain = Some(node);
+                }
+                Err(e) => {
+                    if let Some(head) = chain {
+                        // Safety: the detached chain was just allocated from alloc
+                        unsafe { Self::deallocate_node_chain(head, alloc) }
+                    }
+                    return Err(e);
+                }
+            }
+        }
         let mut node = self;
         while let Some(ref mut n) = node.next {
             node = unsafe { n.as_mut() };
         }
-        for _i in 0..amount {
-            node.next = Self::allocate_next_node(alloc);
-            node = node.next_node_mut().expect("Just allocated");
-        }
+        node.next = chain;
+        Ok(())
     }
 
-    unsafe fn deallocate_node_chain(&mut self, alloc: &Allocator) {
-        if let Some(next) = self.next_node_mut() {
+    /// Takes the node by pointer, since no reference to it may be live while it's freed
+    unsafe fn deallocate_node_chain(node: NonNull<Self>, alloc: &Allocator) {
+        let node = node.as_ptr();
+        if let Some(next) = (*node).next.take() {
             // Safety: any children nodes of a parent node were also allocated
-            next.deallocate_node_chain(alloc);
-            self.next = None;
+            Self::deallocate_node_chain(next, alloc);
         }
-        self.deallocate_node_data(alloc);
-        alloc.deallocate(self as *mut Self, 1);
+        (*node).deallocate_node_data(alloc);
+        alloc.deallocate(node, 1);
     }
 
     unsafe fn deallocate_node_data(&mut self, alloc: &Allocator) {
@@ -89,8 +117,8 @@ where
     /// Safety: only deallocate with the same allocator that allocated.
     /// Only call on the primary node
     pub unsafe fn deallocate(&mut self, alloc: &Allocator) {
-        if let Some(next) = self.next_node_mut() {
-            next.deallocate_node_chain(alloc)
+        if let Some(next) = self.next.take() {
+            Self::deallocate_node_chain(next, alloc)
         }
         self.deallocate_node_data(alloc);
     }
@@ -147,7 +175,7 @@ where
         let mut curr = NonNull::new(&mut self.node as *mut Node<T, N>);
         while let Some(mut node) = curr {
             let next = unsafe { &mut *node.as_ptr() }.next;
-            let len = if let Some(_) = next {
+            let len = if next.is_some() {
                 N::USIZE
             } else {
                 self.length % N::USIZE
@@ -191,40 +219,49 @@ where
         Self::NODE_ARRAY_LEN
     }
 
+    /// Uses the current default resource
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::with_capacity_in(0, a::get_default_resource())
+    }
+
+    /// Panics if the allocation fails
     pub fn with_capacity_in(capacity: usize, alloc: Allocator) -> Self {
-        let (node, capacity) = if capacity <= Self::NODE_ARRAY_LEN {
-            (Node::with_data(&alloc), Self::NODE_ARRAY_LEN)
-        } else {
-            let mut parent = Node::with_data(&alloc);
-            let capacity = {
-                let mut i = 1 as usize;
-                let cap = loop {
-                    i += 1;
-                    if Self::NODE_ARRAY_LEN * i >= capacity {
-                        break Self::NODE_ARRAY_LEN * i;
-                    }
-                };
-                parent.allocate_node_chain(i - 1, &alloc);
-                cap
-            };
-            (parent, capacity)
-        };
-        Self {
+        Self::try_with_capacity_in(capacity, alloc).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_with_capacity_in(capacity: usize, alloc: Allocator) -> Result<Self, AllocError> {
+        let nodes = capacity.div_ceil(Self::NODE_ARRAY_LEN).max(1);
+        let mut node = Node::try_with_data(&alloc)?;
+        if let Err(e) = node.try_allocate_node_chain(nodes - 1, &alloc) {
+            // Safety: the node was just allocated from alloc
+            unsafe { node.deallocate(&alloc) };
+            return Err(e);
+        }
+        Ok(Self {
             length: 0,
-            capacity,
+            capacity: nodes * Self::NODE_ARRAY_LEN,
             node,
             alloc,
-        }
+        })
     }
 
+    /// Panics if the allocation fails
     pub fn push_back(&mut self, val: T) {
+        if let Err(e) = self.try_push_back(val) {
+            panic!("{}", e)
+        }
+    }
+
+    pub fn try_push_back(&mut self, val: T) -> Result<(), AllocError> {
         if self.length >= self.capacity {
             let amount = self.length / self.capacity;
-            self.node.allocate_node_chain(amount, &self.alloc);
+            self.node.try_allocate_node_chain(amount, &self.alloc)?;
             self.capacity += Self::NODE_ARRAY_LEN * amount;
         }
         unsafe { self.node.write_data_unchecked(self.length, val) }
         self.length += 1;
+        Ok(())
     }
 
     pub fn iter<'a>(&'a self) -> SequeIter<'a, T, N> {
@@ -306,7 +343,7 @@ where
         let new_curr = self.next?;
         let new_curr = unsafe { new_curr.as_ref() };
         self.next = new_curr.next;
-        let len = if let Some(_) = self.next {
+        let len = if self.next.is_some() {
             N::USIZE
         } else {
             self.len % N::USIZE
@@ -360,7 +397,7 @@ where
         let mut new_curr = self.next?;
         let new_curr = unsafe { new_curr.as_mut() };
         self.next = new_curr.next;
-        let len = if let Some(_) = self.next {
+        let len = if self.next.is_some() {
             N::USIZE
         } else {
             self.len % N::USIZE
@@ -0,0 +1,109 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+use yaap::a::{self, Allocator, DropArena, NullResource, SystemResource};
+
+mod tracked;
+use tracked::Tracked;
+
+fn system() -> Allocator {
+    Allocator::new(Rc::new(RefCell::new(SystemResource::default())))
+}
+
+/// Records its id in a log when dropped.
+struct Logged<'l>(u32, &'l RefCell<Vec<u32>>);
+
+impl Drop for Logged<'_> {
+    fn drop(&mut self) {
+        self.1.borrow_mut().push(self.0);
+    }
+}
+
+#[test]
+fn test_drop() {
+    static mut COUNTER: isize = 0;
+    struct Test;
+    impl Test {
+        pub fn new() -> Self {
+            unsafe {
+                COUNTER += 1;
+            }
+            Test
+        }
+    }
+    impl Drop for Test {
+        fn drop(&mut self) {
+            unsafe {
+                COUNTER -= 1;
+            }
+        }
+    }
+
+    {
+        let arena = DropArena::new(system());
+        for _i in 0..18 {
+            arena.alloc(Test::new());
+        }
+        assert_eq!(unsafe { COUNTER }, 18);
+    }
+    assert_eq!(unsafe { COUNTER }, 0);
+}
+
+#[test]
+fn reverse_order() {
+    let log = RefCell::new(Vec::new());
+    {
+        let arena = DropArena::new(system());
+        for id in 0..5 {
+            arena.alloc(Logged(id, &log));
+        }
+        assert!(log.borrow().is_empty());
+    }
+    assert_eq!(*log.borrow(), [4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn mixed_types() {
+    let log = RefCell::new(Vec::new());
+    let arena = DropArena::new(system());
+    let byte = arena.alloc(7u8);
+    let text = arena.alloc(String::from("arena"));
+    let wide = arena.alloc([1u64; 4]);
+    let list = arena.alloc(vec![1, 2, 3]);
+    let logged = arena.alloc(Logged(9, &log));
+    let unit = arena.alloc(());
+    assert_eq!(wide.as_ptr() as usize % core::mem::align_of::<u64>(), 0);
+    text.push_str(" text");
+    list.push(4);
+    *byte += 1;
+    assert_eq!((*byte, text.as_str(), *wide), (8, "arena text", [1; 4]));
+    assert_eq!(list, &[1, 2, 3, 4]);
+    assert_eq!((logged.0, *unit), (9, ()));
+    drop(arena);
+    assert_eq!(*log.borrow(), [9]);
+}
+
+#[test]
+fn reset() {
+    let log = RefCell::new(Vec::new());
+    let upstream = Tracked::new(SystemResource::default());
+    let mut arena = DropArena::new(Allocator::new(upstream.clone()));
+    arena.alloc(Logged(1, &log));
+    arena.alloc(Logged(2, &log));
+    assert!(upstream.borrow().count() > 0);
+    arena.reset();
+    assert_eq!(*log.borrow(), [2, 1]);
+    assert_eq!(upstream.borrow().count(), 0);
+    // usable again
+    arena.alloc(Logged(3, &log));
+    drop(arena);
+    assert_eq!(*log.borrow(), [2, 1, 3]);
+    assert_eq!(upstream.borrow().count(), 0);
+}
+
+#[test]
+fn out_of_memory() {
+    let arena = DropArena::new(Allocator::new(Rc::new(RefCell::new(NullResource))));
+    let (value, err) = arena.try_alloc(String::from("kept")).unwrap_err();
+    assert_eq!(value, "kept");
+    assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+}
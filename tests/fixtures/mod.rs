@@ -0,0 +1,48 @@
+// shared by several test binaries, each using only part of it
+#![allow(dead_code)]
+
+use core::{alloc::Layout, mem::MaybeUninit};
+
+/// A buffer aligned well past a word, so block layouts in the tests are predictable.
+#[repr(C, align(16))]
+pub struct Buffer<const N: usize>(pub [MaybeUninit<u8>; N]);
+
+impl<const N: usize> Buffer<N> {
+    pub const fn new() -> Self {
+        Self([MaybeUninit::uninit(); N])
+    }
+}
+
+/// A buffer aligned to a page, for resources that split memory by address bits.
+#[repr(C, align(4096))]
+pub struct PageBuffer<const N: usize>(pub [MaybeUninit<u8>; N]);
+
+impl<const N: usize> PageBuffer<N> {
+    pub const fn new() -> Self {
+        Self([MaybeUninit::uninit(); N])
+    }
+}
+
+/// A fixed linear congruential generator, so failures reproduce.
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    pub fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) as usize % bound
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.next(i + 1));
+        }
+    }
+}
+
+/// A byte aligned layout of `size` bytes.
+pub fn bytes(size: usize) -> Layout {
+    Layout::from_size_align(size, 1).unwrap()
+}
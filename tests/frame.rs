@@ -0,0 +1,111 @@
+use core::{alloc::Layout, cell::RefCell, mem::MaybeUninit, ptr};
+use std::rc::Rc;
+use typenum::U16;
+use yaap::{
+    a::{self, Allocator, FrameResource},
+    prelude::*,
+};
+
+mod deque;
+mod fixtures;
+use deque::Seque;
+use fixtures::{bytes, Buffer};
+
+#[test]
+fn lives_one_more_frame() {
+    let mut buffer = Buffer::<256>::new();
+    let mut res = FrameResource::new(&mut buffer.0);
+    unsafe {
+        let first = res.try_allocate(Layout::new::<u64>()).unwrap().as_ptr() as *mut u64;
+        first.write(7);
+        res.next_frame();
+        let second = res.try_allocate(Layout::new::<u64>()).unwrap().as_ptr() as *mut u64;
+        second.write(8);
+        assert_ne!(first, second);
+        assert_eq!(*first, 7);
+        assert_eq!(res.previous_used(), 8);
+        res.next_frame();
+        assert_eq!(*second, 8);
+        // frame 0's memory is reclaimed for frame 2
+        let third = res.try_allocate(Layout::new::<u64>()).unwrap().as_ptr() as *mut u64;
+        assert_eq!(first, third);
+    }
+    assert_eq!(res.frame(), 2);
+}
+
+#[test]
+fn high_water_marks() {
+    let mut buffer = Buffer::<1024>::new();
+    let mut res = FrameResource::new(&mut buffer.0);
+    let mut marks = Vec::new();
+    unsafe {
+        for size in [100, 40, 300, 0] {
+            res.try_allocate(bytes(size)).unwrap();
+            assert_eq!(res.used(), size);
+            marks.push(res.next_frame());
+        }
+        res.try_allocate(bytes(20)).unwrap();
+    }
+    assert_eq!(marks, [100, 40, 300, 0]);
+    assert_eq!(res.high_water(), 300);
+    unsafe { res.reset() };
+    assert_eq!((res.used(), res.previous_used()), (0, 0));
+    assert_eq!(res.high_water(), 300);
+}
+
+#[test]
+fn exhausted() {
+    let mut buffer = Buffer::<256>::new();
+    let mut res = FrameResource::new(&mut buffer.0);
+    unsafe {
+        res.try_allocate(bytes(100)).unwrap();
+        // each frame gets half the buffer
+        let err = res.try_allocate(bytes(29)).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert!(err.resource().contains("FrameResource"));
+        res.next_frame();
+        res.try_allocate(bytes(128)).unwrap();
+    }
+    assert_eq!(res.remaining(), 0);
+}
+
+#[test]
+fn grow_in_place() {
+    let mut buffer = Buffer::<128>::new();
+    let mut res = FrameResource::new(&mut buffer.0);
+    unsafe {
+        let a = res.try_allocate(bytes(16)).unwrap().as_ptr() as *mut ();
+        assert!(res.try_grow_in_place(a, bytes(16), bytes(64)));
+        assert!(!res.try_grow_in_place(a, bytes(64), bytes(65)));
+        res.next_frame();
+        // ends where the new frame starts, but belongs to the previous one
+        assert!(!res.try_grow_in_place(a, bytes(64), bytes(80)));
+        let b = res.try_allocate(bytes(8)).unwrap().as_ptr() as *mut ();
+        assert!(res.try_grow_in_place(b, bytes(8), bytes(32)));
+        assert_eq!(res.used(), 32);
+    }
+}
+
+#[test]
+fn seque_per_frame() {
+    static mut BUFFER: [MaybeUninit<u8>; 8192] = [MaybeUninit::uninit(); 8192];
+    // Safety: the only reference to the buffer, taken once
+    let buffer = unsafe { &mut *ptr::addr_of_mut!(BUFFER) };
+    let res = Rc::new(RefCell::new(FrameResource::new(buffer)));
+    let mut previous: Option<Seque<usize, U16>> = None;
+    for frame in 0..10 {
+        let mut c = Seque::<usize, U16>::with_capacity_in(1, Allocator::new(res.clone()));
+        for i in 0..40 {
+            c.push_back(frame * 100 + i);
+        }
+        // the previous frame's container is still intact
+        if let Some(previous) = previous.take() {
+            assert!((0..40).all(|i| previous[i] == (frame - 1) * 100 + i));
+        }
+        previous = Some(c);
+        // the container of the frame before was just dropped
+        unsafe { res.borrow_mut().next_frame() };
+    }
+    drop(previous);
+    assert!(res.borrow().high_water() > 0);
+}
@@ -0,0 +1,192 @@
+use core::{alloc::Layout, mem::MaybeUninit};
+use yaap::{
+    a::{self, FreeListResource, Placement},
+    prelude::*,
+};
+
+mod fixtures;
+use fixtures::{bytes, Buffer, Lcg};
+
+/// A block's boundary tag is one word.
+const TAG: usize = core::mem::size_of::<usize>();
+const POLICIES: [Placement; 3] = [Placement::FirstFit, Placement::NextFit, Placement::BestFit];
+
+/// Leaves a large hole, then a small one, each followed by a used block, then the free tail.
+/// Returns the holes.
+unsafe fn holes(res: &mut FreeListResource<'_>) -> (*mut u8, *mut u8) {
+    let large = res.try_allocate(bytes(256)).unwrap().as_ptr() as *mut u8;
+    res.try_allocate(bytes(8)).unwrap();
+    let small = res.try_allocate(bytes(64)).unwrap().as_ptr() as *mut u8;
+    res.try_allocate(bytes(8)).unwrap();
+    res.deallocate(large as *mut (), bytes(256));
+    res.deallocate(small as *mut (), bytes(64));
+    (large, small)
+}
+
+#[test]
+fn one_free_block() {
+    let mut region = Buffer::<4096>::new();
+    let res = FreeListResource::new(&mut region.0, Placement::FirstFit);
+    let stats = res.stats();
+    // the region starts one word before an aligned payload and ends with a sentinel tag
+    assert_eq!(stats.free, 4096 - 16 - TAG);
+    assert_eq!(
+        (stats.free_blocks, stats.used_blocks, stats.used),
+        (1, 0, 0)
+    );
+    assert_eq!(res.policy(), Placement::FirstFit);
+}
+
+#[test]
+fn placement() {
+    for policy in POLICIES {
+        let mut region = Buffer::<4096>::new();
+        let mut res = FreeListResource::new(&mut region.0, policy);
+        unsafe {
+            let (large, small) = holes(&mut res);
+            let p = res.try_allocate(bytes(48)).unwrap().as_ptr() as *mut u8;
+            match policy {
+                Placement::FirstFit => assert_eq!(p, large),
+                // carries on after the last block it placed
+                Placement::NextFit => assert!(p > small),
+                Placement::BestFit => assert_eq!(p, small),
+            }
+        }
+    }
+}
+
+#[test]
+fn set_policy() {
+    let mut region = Buffer::<4096>::new();
+    let mut res = FreeListResource::new(&mut region.0, Placement::FirstFit);
+    unsafe {
+        let (_, small) = holes(&mut res);
+        res.set_policy(Placement::BestFit);
+        let p = res.try_allocate(bytes(48)).unwrap().as_ptr() as *mut u8;
+        assert_eq!(p, small);
+    }
+}
+
+#[test]
+fn coalesces_both_neighbours() {
+    let mut region = Buffer::<4096>::new();
+    let mut res = FreeListResource::new(&mut region.0, Placement::FirstFit);
+    let initial = res.stats();
+    unsafe {
+        let blocks: Vec<_> = (0..4)
+            .map(|_| res.try_allocate(bytes(100)).unwrap().as_ptr() as *mut ())
+            .collect();
+        res.deallocate(blocks[0], bytes(100));
+        res.deallocate(blocks[2], bytes(100));
+        assert_eq!(res.stats().free_blocks, 3);
+        res.deallocate(blocks[1], bytes(100));
+        assert_eq!(res.stats().free_blocks, 2);
+        res.deallocate(blocks[3], bytes(100));
+    }
+    assert_eq!(res.stats(), initial);
+}
+
+#[test]
+fn large_alignment() {
+    for policy in POLICIES {
+        let mut region = Buffer::<8192>::new();
+        let mut res = FreeListResource::new(&mut region.0, policy);
+        let initial = res.stats();
+        let mut live = Vec::new();
+        unsafe {
+            for align in [32, 64, 256, 1024] {
+                live.push((res.try_allocate(bytes(24)).unwrap().as_ptr(), bytes(24)));
+                let layout = Layout::from_size_align(40, align).unwrap();
+                let p = res.try_allocate(layout).unwrap().as_ptr();
+                assert_eq!(p as usize % align, 0);
+                live.push((p, layout));
+            }
+            for (p, layout) in live {
+                res.deallocate(p as *mut (), layout);
+            }
+        }
+        assert_eq!(res.stats(), initial);
+    }
+}
+
+#[test]
+fn grow_in_place() {
+    let mut region = Buffer::<4096>::new();
+    let mut res = FreeListResource::new(&mut region.0, Placement::FirstFit);
+    unsafe {
+        let a = res.try_allocate(bytes(64)).unwrap().as_ptr() as *mut ();
+        assert!(res.try_grow_in_place(a, bytes(64), bytes(1000)));
+        let b = res.try_allocate(bytes(64)).unwrap().as_ptr() as *mut ();
+        // the tag plus 1000 bytes, rounded up to a multiple of 16
+        assert_eq!(b as usize - a as usize, 1008);
+        assert!(!res.try_grow_in_place(a, bytes(1000), bytes(1100)));
+        res.deallocate(b, bytes(64));
+        assert!(res.try_grow_in_place(a, bytes(1000), bytes(2000)));
+        assert_eq!(res.stats().used_blocks, 1);
+    }
+}
+
+#[test]
+fn exhausted() {
+    let mut region = Buffer::<1024>::new();
+    let mut res = FreeListResource::new(&mut region.0, Placement::BestFit);
+    unsafe {
+        let err = res.try_allocate(bytes(2000)).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert!(err.resource().contains("FreeListResource"));
+    }
+    let mut tiny = [MaybeUninit::uninit(); 16];
+    let mut res = FreeListResource::new(&mut tiny, Placement::FirstFit);
+    assert_eq!(res.stats().free_blocks, 0);
+    unsafe { res.try_allocate(bytes(1)).unwrap_err() };
+}
+
+#[test]
+fn shadow_model() {
+    for policy in POLICIES {
+        let mut region = Buffer::<16384>::new();
+        let range = region.0.as_ptr_range();
+        let mut res = FreeListResource::new(&mut region.0, policy);
+        let initial = res.stats();
+        let mut rng = Lcg(7);
+        // every live allocation: address, layout and the byte it was filled with
+        let mut live: Vec<(usize, Layout, u8)> = Vec::new();
+        unsafe {
+            for step in 0..10_000 {
+                if live.is_empty() || rng.next(5) < 3 {
+                    let bound = if rng.next(10) == 0 { 2000 } else { 150 };
+                    let size = rng.next(bound);
+                    let layout = Layout::from_size_align(size, 1 << rng.next(7)).unwrap();
+                    let p = match res.try_allocate(layout) {
+                        Ok(p) => p.as_ptr() as usize,
+                        Err(err) => {
+                            assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+                            continue;
+                        }
+                    };
+                    assert_eq!(p % layout.align(), 0);
+                    assert!(range.start as usize <= p && p + size <= range.end as usize);
+                    for &(q, other, _) in &live {
+                        assert!(p + size.max(1) <= q || q + other.size().max(1) <= p);
+                    }
+                    let fill = step as u8;
+                    (p as *mut u8).write_bytes(fill, size);
+                    live.push((p, layout, fill));
+                } else {
+                    let (p, layout, fill) = live.swap_remove(rng.next(live.len()));
+                    let block = core::slice::from_raw_parts(p as *const u8, layout.size());
+                    assert!(block.iter().all(|&b| b == fill));
+                    res.deallocate(p as *mut (), layout);
+                }
+                let stats = res.stats();
+                assert_eq!(stats.used_blocks, live.len());
+                // coalescing leaves no two free blocks side by side
+                assert!(stats.free_blocks <= stats.used_blocks + 1);
+            }
+            for (p, layout, _) in live {
+                res.deallocate(p as *mut (), layout);
+            }
+        }
+        assert_eq!(res.stats(), initial, "{:?}", policy);
+    }
+}
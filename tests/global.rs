@@ -1,27 +1,10 @@
-use core::{mem::MaybeUninit, ptr::NonNull};
-use std::alloc;
+use core::alloc::Layout;
 use typenum::U16;
 use yaap::{
-    a::{self, Allocator},
+    a::{Allocator, SystemResource},
     prelude::*,
 };
 
-struct Global;
-impl MemoryResource for Global {
-    unsafe fn allocate_bytes(&mut self, size: usize, align: usize) -> a::PtrUninit<()> {
-        NonNull::new(
-            alloc::alloc(alloc::Layout::from_size_align_unchecked(size, align))
-                as *mut MaybeUninit<()>,
-        )
-    }
-    unsafe fn deallocate_bytes(&mut self, pointer: *mut (), size: usize, align: usize) {
-        alloc::dealloc(
-            pointer as *mut u8,
-            alloc::Layout::from_size_align_unchecked(size, align),
-        )
-    }
-}
-
 mod deque;
 mod tracked;
 use deque::Seque;
@@ -29,13 +12,13 @@ use tracked::Tracked;
 
 #[test]
 fn none() {
-    let res = Tracked::new(Global);
+    let res = Tracked::new(SystemResource::default());
     let _c = Seque::<usize, U16>::with_capacity_in(0, Allocator::new(res));
 }
 
 #[test]
 fn single() {
-    let res = Tracked::new(Global);
+    let res = Tracked::new(SystemResource::default());
     let mut c = Seque::<usize, U16>::with_capacity_in(1, Allocator::new(res));
     c.push_back(4);
     assert_eq!(4, c[0]);
@@ -44,7 +27,7 @@ fn single() {
 
 #[test]
 fn reallocate() {
-    let res = Tracked::new(Global);
+    let res = Tracked::new(SystemResource::default());
     let mut c = Seque::<usize, U16>::with_capacity_in(1, Allocator::new(res));
     for i in 0..c.node_array_len() + 1 {
         c.push_back(2 + i);
@@ -52,7 +35,7 @@ fn reallocate() {
 }
 #[test]
 fn large_allocate() {
-    let res = Tracked::new(Global);
+    let res = Tracked::new(SystemResource::default());
     type Seq16 = Seque<usize, U16>;
     let mut c = Seq16::with_capacity_in(Seq16::NODE_ARRAY_LEN * 2, Allocator::new(res));
     for i in 0..Seq16::NODE_ARRAY_LEN + 2 {
@@ -71,7 +54,7 @@ fn large_allocate() {
 #[test]
 #[should_panic(expected = "index out of bounds")]
 fn no_push() {
-    let res = Tracked::new(Global);
+    let res = Tracked::new(SystemResource::default());
     let mut c = Seque::<u8, U16>::with_capacity_in(1, Allocator::new(res));
     c[0] = 5;
 }
@@ -79,7 +62,144 @@ fn no_push() {
 #[test]
 #[should_panic(expected = "index out of bounds")]
 fn no_push_empty() {
-    let res = Tracked::new(Global);
+    let res = Tracked::new(SystemResource::default());
     let mut c = Seque::<u8, U16>::with_capacity_in(0, Allocator::new(res));
     c[0] = 5;
 }
+
+#[test]
+fn overflowing_allocate() {
+    let res = Tracked::new(SystemResource::default());
+    let alloc = Allocator::new(res.clone());
+    let p = unsafe { alloc.allocate::<u64>(usize::MAX / 4) };
+    assert!(p.is_none());
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn layout_allocate() {
+    let res = Tracked::new(SystemResource::default());
+    let alloc = Allocator::new(res.clone());
+    let layout = Layout::from_size_align(24, 8).unwrap();
+    unsafe {
+        let p = alloc.allocate_layout(layout).expect("allocation");
+        assert_eq!(p.as_ptr() as usize % 8, 0);
+        assert_eq!(res.borrow().count(), 24);
+        alloc.deallocate_layout(p.as_ptr() as *mut (), layout);
+    }
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn invalid_align() {
+    let res = Tracked::new(SystemResource::default());
+    let alloc = Allocator::new(res.clone());
+    assert!(unsafe { alloc.allocate_bytes(8, 3) }.is_none());
+}
+
+#[test]
+fn equality() {
+    let alloc = Allocator::new(Tracked::new(SystemResource::default()));
+    let other = Allocator::new(Tracked::new(SystemResource::default()));
+    assert!(alloc == alloc.clone());
+    assert!(alloc != other);
+    assert!(alloc.get().is_equal(&*alloc.get()));
+    assert!(!alloc.get().is_equal(&*other.get()));
+}
+
+#[test]
+fn container_allocator_equality() {
+    let alloc = Allocator::new(Tracked::new(SystemResource::default()));
+    let c = Seque::<usize, U16>::with_capacity_in(1, alloc.clone());
+    let d = Seque::<usize, U16>::with_capacity_in(
+        1,
+        Allocator::new(Tracked::new(SystemResource::default())),
+    );
+    assert!(c.allocator() == alloc);
+    assert!(c.allocator() != d.allocator());
+}
+
+#[test]
+fn zero_sized() {
+    let res = Tracked::new(SystemResource::default());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.allocate::<u64>(0).expect("zero sized allocation");
+        assert_eq!(p.as_ptr() as usize % 8, 0);
+        alloc.deallocate(p.as_ptr(), 0);
+    }
+}
+
+#[test]
+fn system_zeroed() {
+    let mut res = SystemResource::default();
+    let layout = Layout::array::<u32>(64).unwrap();
+    unsafe {
+        let p = res.allocate_zeroed(layout).unwrap().as_ptr() as *mut u32;
+        assert!((0..64).all(|i| *p.add(i) == 0));
+        res.deallocate(p as *mut (), layout);
+    }
+}
+
+#[test]
+fn system_reallocate() {
+    let mut res = SystemResource::default();
+    let layout = Layout::array::<u32>(4).unwrap();
+    unsafe {
+        let p = res.try_allocate(layout).unwrap().as_ptr() as *mut u32;
+        for i in 0..4 {
+            p.add(i).write(i as u32);
+        }
+        let q = res
+            .reallocate(p as *mut (), layout, 1024 * 4)
+            .unwrap()
+            .as_ptr() as *mut u32;
+        assert!((0..4).all(|i| *q.add(i) == i as u32));
+        let r = res.reallocate(q as *mut (), Layout::array::<u32>(1024).unwrap(), 8);
+        let r = r.unwrap().as_ptr() as *mut u32;
+        assert_eq!(*r.add(1), 1);
+        res.deallocate(r as *mut (), Layout::array::<u32>(2).unwrap());
+    }
+}
+
+#[test]
+fn grow_and_shrink() {
+    let res = Tracked::new(SystemResource::default());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u16>(3).unwrap().as_ptr() as *mut u16;
+        for i in 0..3 {
+            p.add(i).write(i as u16 + 1);
+        }
+        let q = alloc.grow_zeroed(p, 3, 1000).unwrap().as_ptr() as *mut u16;
+        assert_eq!(res.borrow().count(), 2000);
+        assert_eq!((*q, *q.add(2), *q.add(3), *q.add(999)), (1, 3, 0, 0));
+        let r = alloc.shrink(q, 1000, 2).unwrap().as_ptr() as *mut u16;
+        assert_eq!((*r, *r.add(1)), (1, 2));
+        alloc.deallocate(r, 2);
+    }
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn grow_overflow() {
+    let alloc = Allocator::new(Tracked::new(SystemResource::default()));
+    unsafe {
+        let p = alloc.try_allocate::<u64>(1).unwrap().as_ptr();
+        assert!(alloc.grow(p, 1, usize::MAX).is_err());
+        alloc.deallocate(p, 1);
+    }
+}
+
+#[test]
+fn typed_zeroed() {
+    let res = Tracked::new(SystemResource::default());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.allocate_zeroed::<usize>(512).unwrap().as_ptr() as *mut usize;
+        assert!((0..512).all(|i| *p.add(i) == 0));
+        assert_eq!(res.borrow().count(), 512 * core::mem::size_of::<usize>());
+        alloc.deallocate(p, 512);
+    }
+    assert_eq!(res.borrow().count(), 0);
+}
@@ -0,0 +1,86 @@
+use core::alloc::Layout;
+use std::{alloc::System, sync::Mutex};
+use yaap::{
+    a::{self, ResourceGlobalAlloc, SystemResource},
+    prelude::*,
+};
+
+/// Process-wide accounting; only ever grows so that concurrent tests can't make checks flaky
+#[derive(Clone, Copy, Debug)]
+struct Counts {
+    allocations: usize,
+    deallocations: usize,
+    bytes_allocated: usize,
+    bytes_deallocated: usize,
+}
+
+struct Counting<T>(T, Counts);
+
+impl<T> Counting<T> {
+    const fn new(inner: T) -> Self {
+        Counting(
+            inner,
+            Counts {
+                allocations: 0,
+                deallocations: 0,
+                bytes_allocated: 0,
+                bytes_deallocated: 0,
+            },
+        )
+    }
+}
+
+impl<T> MemoryResource for Counting<T>
+where
+    T: MemoryResource,
+{
+    unsafe fn try_allocate(&mut self, layout: Layout) -> a::AllocResult<()> {
+        let p = self.0.try_allocate(layout);
+        if p.is_ok() {
+            self.1.allocations += 1;
+            self.1.bytes_allocated += layout.size();
+        }
+        p
+    }
+
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
+        self.1.deallocations += 1;
+        self.1.bytes_deallocated += layout.size();
+        self.0.deallocate(pointer, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: ResourceGlobalAlloc<Mutex<Counting<SystemResource>>> =
+    ResourceGlobalAlloc::new(Mutex::new(Counting::new(SystemResource::new(System))));
+
+fn counts() -> Counts {
+    GLOBAL.get().lock().unwrap().1
+}
+
+#[test]
+fn counts_allocations() {
+    const LEN: usize = 1 << 20;
+    let before = counts();
+    let v = vec![1u8; LEN];
+    let during = counts();
+    assert!(during.allocations > before.allocations);
+    assert!(during.bytes_allocated >= before.bytes_allocated + LEN);
+    assert_eq!(v.iter().map(|&b| b as usize).sum::<usize>(), LEN);
+    drop(v);
+    let after = counts();
+    assert!(after.deallocations > during.deallocations);
+    assert!(after.bytes_deallocated >= during.bytes_deallocated + LEN);
+}
+
+#[test]
+fn counts_boxes() {
+    let before = counts();
+    let boxes: Vec<_> = (0..100u64).map(Box::new).collect();
+    let during = counts();
+    assert!(during.allocations >= before.allocations + 101);
+    assert!(during.bytes_allocated >= before.bytes_allocated + 100 * 8);
+    assert_eq!(boxes.iter().map(|b| **b).sum::<u64>(), 4950);
+    drop(boxes);
+    assert!(counts().deallocations >= during.deallocations + 101);
+}
@@ -0,0 +1,146 @@
+use core::{alloc::Layout, cell::RefCell, mem::MaybeUninit, ptr};
+use std::rc::Rc;
+use typenum::U16;
+use yaap::{
+    a::{self, Allocator, MonotonicBufferResource, NullResource, SystemResource},
+    prelude::*,
+};
+
+mod deque;
+mod fixtures;
+mod tracked;
+use deque::Seque;
+use fixtures::Buffer;
+use tracked::Tracked;
+
+fn null() -> Allocator {
+    Allocator::new(Rc::new(RefCell::new(NullResource)))
+}
+
+#[test]
+fn buffer_first() {
+    let mut buffer = [MaybeUninit::<u8>::uninit(); 256];
+    let range = buffer.as_ptr_range();
+    let mut resource = MonotonicBufferResource::with_buffer(&mut buffer, null());
+    unsafe {
+        let p = resource
+            .try_allocate(Layout::new::<[u64; 8]>())
+            .unwrap()
+            .as_ptr() as *const MaybeUninit<u8>;
+        assert!(range.contains(&p));
+        assert_eq!(p as usize % 8, 0);
+    }
+}
+
+#[test]
+fn padding_counts_against_capacity() {
+    let mut buffer = Buffer::<32>::new();
+    let mut resource = MonotonicBufferResource::with_buffer(&mut buffer.0, null());
+    unsafe {
+        resource.try_allocate(Layout::new::<u8>()).unwrap();
+        // 15 bytes of padding, then 16 bytes: fits exactly
+        resource
+            .try_allocate(Layout::from_size_align(16, 16).unwrap())
+            .unwrap();
+        let err = resource.try_allocate(Layout::new::<u8>()).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert!(err.resource().ends_with("NullResource"));
+    }
+}
+
+#[test]
+fn chunks_grow_geometrically() {
+    let upstream = Tracked::new(SystemResource::default());
+    let alloc = Allocator::new(Rc::new(RefCell::new(
+        MonotonicBufferResource::with_initial_size(64, Allocator::new(upstream.clone())),
+    )));
+    unsafe {
+        alloc.try_allocate::<u8>(1).unwrap();
+        assert_eq!(upstream.borrow().count(), 64);
+        alloc.try_allocate::<u8>(64).unwrap();
+        assert_eq!(upstream.borrow().count(), 64 + 128);
+        alloc.try_allocate::<u8>(128).unwrap();
+        assert_eq!(upstream.borrow().count(), 64 + 128 + 256);
+        // larger than the next chunk: the chunk is sized to fit
+        alloc.try_allocate::<u8>(4096).unwrap();
+        assert!(upstream.borrow().count() > 64 + 128 + 256 + 4096);
+    }
+}
+
+#[test]
+fn release_returns_every_chunk() {
+    let upstream = Tracked::new(SystemResource::default());
+    let resource = Rc::new(RefCell::new(MonotonicBufferResource::with_initial_size(
+        32,
+        Allocator::new(upstream.clone()),
+    )));
+    let alloc = Allocator::new(resource.clone());
+    unsafe {
+        for _ in 0..10 {
+            alloc.try_allocate::<u64>(10).unwrap();
+        }
+    }
+    assert!(upstream.borrow().count() > 0);
+    unsafe { resource.borrow_mut().release() };
+    assert_eq!(upstream.borrow().count(), 0);
+    unsafe {
+        alloc.try_allocate::<u8>(1).unwrap();
+    }
+    // back to the initial chunk size
+    assert_eq!(upstream.borrow().count(), 32);
+}
+
+#[test]
+fn drop_releases() {
+    let upstream = Tracked::new(SystemResource::default());
+    {
+        let mut c = Seque::<usize, U16>::with_capacity_in(
+            1,
+            Allocator::new(Rc::new(RefCell::new(MonotonicBufferResource::new(
+                Allocator::new(upstream.clone()),
+            )))),
+        );
+        for i in 0..100 {
+            c.push_back(i);
+        }
+        assert_eq!(c[99], 99);
+        assert!(upstream.borrow().count() > 0);
+    }
+    assert_eq!(upstream.borrow().count(), 0);
+}
+
+#[test]
+fn grow_last_in_place() {
+    let alloc = Allocator::new(Rc::new(RefCell::new(MonotonicBufferResource::new(
+        Allocator::new(Tracked::new(SystemResource::default())),
+    ))));
+    unsafe {
+        let p = alloc.try_allocate::<u32>(4).unwrap().as_ptr() as *mut u32;
+        assert!(alloc.try_grow_in_place(p, 4, 16));
+        let q = alloc.try_allocate::<u32>(1).unwrap().as_ptr() as *mut u32;
+        assert!(!alloc.try_grow_in_place(p, 16, 32));
+        assert!(alloc.try_grow_in_place(q, 1, 2));
+    }
+}
+
+#[test]
+fn chained_arenas() {
+    static mut OUTER: [MaybeUninit<u8>; 512] = [MaybeUninit::uninit(); 512];
+    // Safety: the only reference to the buffer, taken once
+    let outer = unsafe { &mut *ptr::addr_of_mut!(OUTER) };
+    let outer = Allocator::new(Rc::new(RefCell::new(MonotonicBufferResource::with_buffer(
+        outer,
+        null(),
+    ))));
+    let alloc = Allocator::new(Rc::new(RefCell::new(
+        MonotonicBufferResource::with_initial_size(64, outer),
+    )));
+    let c = Seque::<usize, U16>::try_with_capacity_in(16, alloc.clone());
+    assert!(c.is_ok());
+    drop(c);
+    let err = Seque::<usize, U16>::try_with_capacity_in(64, alloc)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+    assert!(err.resource().ends_with("NullResource"));
+}
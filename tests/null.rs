@@ -0,0 +1,58 @@
+use core::alloc::Layout;
+use core::cell::RefCell;
+use std::{rc::Rc, sync::Arc};
+use typenum::U16;
+use yaap::a::{self, Allocator, DefaultResourceGuard, NullResource, SyncAllocator};
+
+mod deque;
+use deque::Seque;
+
+fn null() -> Allocator {
+    Allocator::new(Rc::new(RefCell::new(NullResource)))
+}
+
+#[test]
+fn refuses_everything() {
+    let alloc = null();
+    unsafe {
+        assert!(alloc.allocate::<u8>(1).is_none());
+        assert!(alloc.allocate::<u8>(0).is_none());
+        let err = alloc.try_allocate::<u64>(4).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert_eq!(err.layout(), Some(Layout::array::<u64>(4).unwrap()));
+        assert!(err.resource().ends_with("NullResource"));
+        assert!(alloc.allocate_zeroed::<u64>(4).is_err());
+    }
+}
+
+#[test]
+fn sync_refuses_everything() {
+    let alloc = SyncAllocator::new(Arc::new(NullResource));
+    assert!(unsafe { alloc.try_allocate::<u32>(1) }.is_err());
+}
+
+#[test]
+fn deallocate_null() {
+    unsafe { null().deallocate_layout(core::ptr::null_mut(), Layout::new::<u64>()) };
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "NullResource never allocates")]
+fn deallocate_non_null() {
+    let mut value = 0u64;
+    unsafe { null().deallocate(&mut value as *mut u64, 1) };
+}
+
+#[test]
+fn container_reports_failure() {
+    let c = Seque::<usize, U16>::try_with_capacity_in(40, null());
+    assert_eq!(c.err().unwrap().kind(), a::AllocErrorKind::Exhausted);
+}
+
+#[test]
+#[should_panic(expected = "NullResource could not allocate 128 bytes aligned to 8")]
+fn container_panics_with_reason() {
+    let _guard = DefaultResourceGuard::new(null());
+    let _c = Seque::<usize, U16>::new();
+}
@@ -0,0 +1,227 @@
+use core::{alloc::Layout, cell::RefCell};
+use std::{collections::HashSet, rc::Rc, sync::Arc, thread};
+use typenum::U16;
+use yaap::a::{
+    Allocator, PoolOptions, SyncAllocator, SynchronizedPoolResource, SystemResource,
+    UnsynchronizedPoolResource,
+};
+
+mod deque;
+mod tracked;
+use deque::Seque;
+use tracked::Tracked;
+
+type Upstream = Rc<RefCell<Tracked<SystemResource>>>;
+
+fn pool(options: PoolOptions) -> (Upstream, Rc<RefCell<UnsynchronizedPoolResource>>) {
+    let upstream = Tracked::new(SystemResource::default());
+    let pool = UnsynchronizedPoolResource::with_options(options, Allocator::new(upstream.clone()));
+    (upstream, Rc::new(RefCell::new(pool)))
+}
+
+// a chunk holds its blocks and a trailer of a link and a layout
+const TRAILER: usize = 3 * core::mem::size_of::<usize>();
+
+#[test]
+fn options_normalized() {
+    let (_, res) = pool(PoolOptions {
+        max_blocks_per_chunk: 0,
+        largest_required_pool_block: 100,
+    });
+    let options = res.borrow().options();
+    assert_eq!(
+        options.max_blocks_per_chunk,
+        PoolOptions::default().max_blocks_per_chunk
+    );
+    assert_eq!(options.largest_required_pool_block, 128);
+}
+
+#[test]
+fn blocks_are_reused() {
+    let (upstream, res) = pool(PoolOptions::default());
+    let alloc = Allocator::new(res);
+    unsafe {
+        let p = alloc.try_allocate::<u64>(1).unwrap().as_ptr();
+        assert_eq!(upstream.borrow().count(), 8 * 8 + TRAILER);
+        alloc.deallocate(p as *mut u64, 1);
+        let q = alloc.try_allocate::<u64>(1).unwrap().as_ptr();
+        assert_eq!(p, q);
+        assert_eq!(upstream.borrow().count(), 8 * 8 + TRAILER);
+        alloc.deallocate(q as *mut u64, 1);
+    }
+}
+
+#[test]
+fn chunks_grow_until_max() {
+    let (upstream, res) = pool(PoolOptions {
+        max_blocks_per_chunk: 16,
+        largest_required_pool_block: 64,
+    });
+    let alloc = Allocator::new(res);
+    let block = Layout::new::<[u8; 32]>();
+    let mut expected = 0;
+    unsafe {
+        let mut blocks = Vec::new();
+        for chunk_blocks in [8, 16, 16] {
+            expected += chunk_blocks * 32 + TRAILER;
+            for _ in 0..chunk_blocks {
+                blocks.push(alloc.try_allocate_layout(block).unwrap());
+                assert_eq!(upstream.borrow().count(), expected);
+            }
+        }
+        for p in blocks {
+            alloc.deallocate_layout(p.as_ptr() as *mut (), block);
+        }
+    }
+    assert_eq!(upstream.borrow().count(), expected);
+}
+
+#[test]
+fn blocks_aligned_to_class() {
+    let (_, res) = pool(PoolOptions::default());
+    let alloc = Allocator::new(res);
+    unsafe {
+        for shift in 0..=12 {
+            let layout = Layout::from_size_align(1, 1 << shift).unwrap();
+            let p = alloc.try_allocate_layout(layout).unwrap().as_ptr() as usize;
+            assert_eq!(p % layout.align(), 0);
+            // blocks are aligned to their size too
+            let layout = Layout::from_size_align(1 << shift, 1).unwrap();
+            let q = alloc.try_allocate_layout(layout).unwrap().as_ptr() as usize;
+            assert_eq!(q % layout.size().max(8), 0);
+        }
+    }
+}
+
+#[test]
+fn oversized_goes_upstream() {
+    let (upstream, res) = pool(PoolOptions::default());
+    let alloc = Allocator::new(res);
+    unsafe {
+        let p = alloc.try_allocate::<u8>(4097).unwrap().as_ptr();
+        assert_eq!(upstream.borrow().count(), 4097);
+        alloc.deallocate(p as *mut u8, 4097);
+        assert_eq!(upstream.borrow().count(), 0);
+        let layout = Layout::from_size_align(8, 8192).unwrap();
+        let p = alloc.try_allocate_layout(layout).unwrap().as_ptr();
+        assert_eq!(upstream.borrow().count(), 8);
+        alloc.deallocate_layout(p as *mut (), layout);
+    }
+}
+
+#[test]
+fn release_returns_every_chunk() {
+    let (upstream, res) = pool(PoolOptions::default());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        for size in 1..200 {
+            alloc.try_allocate::<u8>(size).unwrap();
+        }
+    }
+    assert!(upstream.borrow().count() > 0);
+    unsafe { res.borrow_mut().release() };
+    assert_eq!(upstream.borrow().count(), 0);
+    unsafe {
+        alloc.try_allocate::<u64>(1).unwrap();
+    }
+    assert_eq!(upstream.borrow().count(), 8 * 8 + TRAILER);
+}
+
+#[test]
+fn seque_reuses_nodes() {
+    let (upstream, res) = pool(PoolOptions::default());
+    let alloc = Allocator::new(res);
+    let fill = || {
+        let mut c = Seque::<usize, U16>::with_capacity_in(1, alloc.clone());
+        for i in 0..200 {
+            c.push_back(i);
+        }
+        assert!((0..200).all(|i| c[i] == i));
+    };
+    fill();
+    let used = upstream.borrow().count();
+    fill();
+    assert_eq!(upstream.borrow().count(), used);
+}
+
+/// Fills every word of a block with its tag.
+unsafe fn tag(address: usize, words: usize, tag: usize) {
+    for word in 0..words {
+        (address as *mut usize).add(word).write(tag);
+    }
+}
+
+unsafe fn check(address: usize, words: usize, tag: usize) {
+    assert!((0..words).all(|word| *(address as *const usize).add(word) == tag));
+}
+
+#[test]
+fn synchronized_stress() {
+    const THREADS: usize = 8;
+    const ROUNDS: usize = 2000;
+    let upstream = Tracked::new_sync(SystemResource::default());
+    let pool = Arc::new(SynchronizedPoolResource::with_shards(
+        4,
+        PoolOptions::default(),
+        SyncAllocator::new(upstream.clone()),
+    ));
+    assert_eq!(pool.shard_count(), 4);
+    let alloc = SyncAllocator::new(pool.clone());
+    let run = |round: usize| {
+        let threads: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let alloc = alloc.clone();
+                thread::spawn(move || {
+                    let mut live = Vec::new();
+                    for i in 0..ROUNDS {
+                        let words = i % 9 + 1;
+                        let id = (round * THREADS + t) * ROUNDS + i;
+                        unsafe {
+                            let p = alloc.try_allocate::<usize>(words).unwrap().as_ptr() as usize;
+                            tag(p, words, id);
+                            live.push((p, words, id));
+                            if i % 3 == 0 {
+                                let (p, words, id) = live.swap_remove(i % live.len());
+                                check(p, words, id);
+                                alloc.deallocate(p as *mut usize, words);
+                            }
+                        }
+                    }
+                    live
+                })
+            })
+            .collect();
+        let live: Vec<_> = threads
+            .into_iter()
+            .flat_map(|t| t.join().unwrap())
+            .collect();
+        let unique: HashSet<_> = live.iter().map(|&(p, _, _)| p).collect();
+        assert_eq!(unique.len(), live.len());
+        // freed here, on another thread than the one that allocated
+        for (p, words, id) in live {
+            unsafe {
+                check(p, words, id);
+                alloc.deallocate(p as *mut usize, words);
+            }
+        }
+    };
+    run(0);
+    run(1);
+    drop(alloc);
+    drop(pool);
+    assert_eq!(upstream.lock().unwrap().count(), 0);
+}
+
+#[test]
+fn synchronized_oversized_goes_upstream() {
+    let upstream = Tracked::new_sync(SystemResource::default());
+    let alloc = SyncAllocator::new(Arc::new(SynchronizedPoolResource::new(SyncAllocator::new(
+        upstream.clone(),
+    ))));
+    unsafe {
+        let p = alloc.try_allocate::<u8>(5000).unwrap().as_ptr();
+        assert_eq!(upstream.lock().unwrap().count(), 5000);
+        alloc.deallocate(p as *mut u8, 5000);
+        assert_eq!(upstream.lock().unwrap().count(), 0);
+    }
+}
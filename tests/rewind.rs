@@ -0,0 +1,186 @@
+use core::{cell::RefCell, mem::MaybeUninit, ptr};
+use std::rc::Rc;
+use typenum::U16;
+use yaap::{
+    a::{
+        Allocator, ArenaResource, ArenaScope, ArrayArenaResource, FrameResource,
+        MonotonicBufferResource, Rewind, StackEnd, StackResource, SystemResource,
+    },
+    prelude::*,
+};
+
+mod deque;
+mod fixtures;
+mod tracked;
+use deque::Seque;
+use fixtures::bytes;
+use tracked::Tracked;
+
+#[test]
+fn arena() {
+    let mut buffer = [MaybeUninit::uninit(); 256];
+    let mut res = ArenaResource::new(&mut buffer);
+    unsafe {
+        res.try_allocate(bytes(10)).unwrap();
+        let checkpoint = res.checkpoint();
+        let a = res.try_allocate(bytes(50)).unwrap();
+        res.try_allocate(bytes(50)).unwrap();
+        res.rewind(checkpoint);
+        assert_eq!(res.used(), 10);
+        let b = res.try_allocate(bytes(50)).unwrap();
+        assert_eq!(a, b);
+        // rewinding to the same point again is fine
+        res.rewind(checkpoint);
+        res.rewind(checkpoint);
+    }
+    assert_eq!(res.used(), 10);
+}
+
+#[test]
+#[should_panic(expected = "rewound past the checkpoint")]
+fn past_checkpoint() {
+    let mut buffer = [MaybeUninit::uninit(); 64];
+    let mut res = ArenaResource::new(&mut buffer);
+    unsafe { res.try_allocate(bytes(10)).unwrap() };
+    let checkpoint = res.checkpoint();
+    unsafe {
+        res.reset();
+        res.rewind(checkpoint);
+    }
+}
+
+#[test]
+fn nested_scopes() {
+    let res = RefCell::new(ArrayArenaResource::<512>::new());
+    let outer = unsafe { ArenaScope::new(&res) };
+    unsafe { res.borrow_mut().try_allocate(bytes(32)).unwrap() };
+    {
+        let _inner = unsafe { ArenaScope::new(&res) };
+        unsafe { res.borrow_mut().try_allocate(bytes(100)).unwrap() };
+        assert_eq!(res.borrow().used(), 132);
+    }
+    assert_eq!(res.borrow().used(), 32);
+    {
+        let inner = unsafe { ArenaScope::new(outer.resource()) };
+        unsafe { res.borrow_mut().try_allocate(bytes(8)).unwrap() };
+        inner.keep();
+    }
+    assert_eq!(res.borrow().used(), 40);
+    drop(outer);
+    assert_eq!(res.borrow().used(), 0);
+}
+
+/// Parses a run of digits into a container, backtracking when it isn't followed by `;`.
+fn digits(input: &str, res: &Rc<RefCell<ArenaResource<'static>>>) -> Option<Seque<u8, U16>> {
+    // Safety: the container is dropped before the scope, unless both are kept
+    let scope = unsafe { ArenaScope::new(res) };
+    let mut c = Seque::with_capacity_in(1, Allocator::new(res.clone()));
+    let rest = input.trim_start_matches(|ch: char| ch.is_ascii_digit());
+    for digit in input[..input.len() - rest.len()].bytes() {
+        c.push_back(digit - b'0');
+    }
+    if rest.starts_with(';') {
+        scope.keep();
+        Some(c)
+    } else {
+        drop(c);
+        None
+    }
+}
+
+#[test]
+fn backtracking() {
+    static mut BUFFER: [MaybeUninit<u8>; 4096] = [MaybeUninit::uninit(); 4096];
+    // Safety: the only reference to the buffer, taken once
+    let buffer = unsafe { &mut *ptr::addr_of_mut!(BUFFER) };
+    let res = Rc::new(RefCell::new(ArenaResource::new(buffer)));
+    assert!(digits("1234567890123456789x", &res).is_none());
+    assert_eq!(res.borrow().used(), 0);
+    let kept = digits("42;", &res).unwrap();
+    let used = res.borrow().used();
+    assert!(used > 0);
+    assert!(digits("0000000000000000000000", &res).is_none());
+    assert_eq!(res.borrow().used(), used);
+    assert_eq!((kept[0], kept[1]), (4, 2));
+}
+
+#[test]
+fn monotonic_reuses_chunks() {
+    let upstream = Tracked::new(SystemResource::default());
+    let mut buffer = [MaybeUninit::uninit(); 64];
+    let start = buffer.as_ptr();
+    let mut res =
+        MonotonicBufferResource::with_buffer(&mut buffer, Allocator::new(upstream.clone()));
+    unsafe {
+        res.try_allocate(bytes(16)).unwrap();
+        let checkpoint = res.checkpoint();
+        let mut first = Vec::new();
+        for _ in 0..20 {
+            first.push(res.try_allocate(bytes(100)).unwrap());
+        }
+        let taken = upstream.borrow().count();
+        assert!(taken > 0);
+        for _ in 0..3 {
+            res.rewind(checkpoint);
+            let again: Vec<_> = (0..20)
+                .map(|_| res.try_allocate(bytes(100)).unwrap())
+                .collect();
+            // the same chunks, in the same order, with nothing more from upstream
+            assert_eq!(again, first);
+            assert_eq!(upstream.borrow().count(), taken);
+        }
+        // back into the initial buffer, right after the first allocation
+        res.rewind(checkpoint);
+        let next = res.try_allocate(bytes(8)).unwrap().as_ptr() as *const MaybeUninit<u8>;
+        assert_eq!(next, start.wrapping_add(16));
+    }
+    drop(res);
+    assert_eq!(upstream.borrow().count(), 0);
+}
+
+#[test]
+fn stack_both_ends() {
+    let mut buffer = [MaybeUninit::uninit(); 256];
+    let mut res = StackResource::new(&mut buffer);
+    unsafe {
+        res.try_allocate(bytes(16)).unwrap();
+        res.set_end(StackEnd::High);
+        res.try_allocate(bytes(16)).unwrap();
+        let (low, high) = (res.used_low(), res.used_high());
+        let checkpoint = res.checkpoint();
+        res.try_allocate(bytes(40)).unwrap();
+        res.set_end(StackEnd::Low);
+        let a = res.try_allocate(bytes(40)).unwrap();
+        res.rewind(checkpoint);
+        assert_eq!((res.used_low(), res.used_high()), (low, high));
+        assert_eq!(res.try_allocate(bytes(40)).unwrap(), a);
+    }
+}
+
+#[test]
+fn frame() {
+    let mut buffer = [MaybeUninit::uninit(); 256];
+    let mut res = FrameResource::new(&mut buffer);
+    unsafe {
+        res.try_allocate(bytes(8)).unwrap();
+        let checkpoint = res.checkpoint();
+        res.try_allocate(bytes(80)).unwrap();
+        res.rewind(checkpoint);
+        assert_eq!(res.used(), 8);
+        // the high-water mark still counts what was rewound
+        assert_eq!(res.high_water(), 88);
+        assert_eq!(res.next_frame(), 88);
+    }
+}
+
+#[test]
+#[should_panic(expected = "checkpoint from another frame")]
+fn frame_checkpoint_expires() {
+    let mut buffer = [MaybeUninit::uninit(); 256];
+    let mut res = FrameResource::new(&mut buffer);
+    let checkpoint = res.checkpoint();
+    unsafe {
+        res.next_frame();
+        res.rewind(checkpoint);
+    }
+}
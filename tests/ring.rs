@@ -0,0 +1,178 @@
+use core::alloc::Layout;
+use std::collections::VecDeque;
+use yaap::{
+    a::{self, RingResource},
+    prelude::*,
+};
+
+mod fixtures;
+use fixtures::{bytes, Buffer, Lcg};
+
+/// A block's header is one word.
+const HEADER: usize = core::mem::size_of::<usize>();
+
+#[test]
+fn fifo() {
+    let mut buffer = Buffer::<256>::new();
+    let mut res = RingResource::new(&mut buffer.0);
+    assert_eq!(res.capacity(), 256);
+    unsafe {
+        let a = res.try_allocate(bytes(10)).unwrap().as_ptr();
+        let b = res.try_allocate(bytes(16)).unwrap().as_ptr();
+        // a header, 10 bytes padded to a word, a header and 16 bytes
+        assert_eq!(res.used(), HEADER + 16 + HEADER + 16);
+        res.deallocate(a as *mut (), bytes(10));
+        assert_eq!(res.used(), HEADER + 16);
+        res.deallocate(b as *mut (), bytes(16));
+    }
+    assert!(res.is_empty());
+}
+
+#[test]
+fn deferred_free() {
+    let mut buffer = Buffer::<256>::new();
+    let mut res = RingResource::new(&mut buffer.0);
+    unsafe {
+        let blocks: Vec<_> = (0..3)
+            .map(|_| res.try_allocate(bytes(24)).unwrap().as_ptr() as *mut ())
+            .collect();
+        let used = res.used();
+        res.deallocate(blocks[1], bytes(24));
+        // waits for the older block
+        assert_eq!(res.used(), used);
+        res.deallocate(blocks[0], bytes(24));
+        assert_eq!(res.used(), HEADER + 24);
+        res.deallocate(blocks[2], bytes(24));
+    }
+    assert!(res.is_empty());
+}
+
+#[test]
+fn wraps_around() {
+    let mut buffer = Buffer::<128>::new();
+    let start = buffer.0.as_ptr() as usize;
+    let mut res = RingResource::new(&mut buffer.0);
+    unsafe {
+        let a = res.try_allocate(bytes(48)).unwrap().as_ptr() as *mut ();
+        let b = res.try_allocate(bytes(48)).unwrap().as_ptr() as *mut ();
+        res.deallocate(a, bytes(48));
+        // 16 bytes are left at the end, so it goes in front of `b`
+        let c = res.try_allocate(bytes(40)).unwrap().as_ptr() as usize;
+        assert_eq!(c, start + HEADER);
+        assert_eq!(res.used(), (HEADER + 48) + 16 + (HEADER + 40));
+        // the filler goes with `b`
+        res.deallocate(b, bytes(48));
+        assert_eq!(res.used(), HEADER + 40);
+        res.deallocate(c as *mut (), bytes(40));
+    }
+    assert!(res.is_empty());
+}
+
+#[test]
+fn full_leaves_live_data() {
+    let mut buffer = Buffer::<512>::new();
+    let mut res = RingResource::new(&mut buffer.0);
+    let mut live = Vec::new();
+    unsafe {
+        let err = loop {
+            match res.try_allocate(Layout::new::<[u64; 3]>()) {
+                Ok(p) => {
+                    let p = p.as_ptr() as *mut [u64; 3];
+                    p.write([live.len() as u64; 3]);
+                    live.push(p);
+                }
+                Err(err) => break err,
+            }
+        };
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert!(err.resource().contains("RingResource"));
+        assert_eq!(live.len(), 512 / 32);
+        assert_eq!(res.used(), res.capacity());
+        res.try_allocate(bytes(1)).unwrap_err();
+        assert!(live.iter().enumerate().all(|(i, &p)| *p == [i as u64; 3]));
+        // freeing the oldest makes room again
+        res.deallocate(live[0] as *mut (), Layout::new::<[u64; 3]>());
+        res.try_allocate(Layout::new::<[u64; 3]>()).unwrap();
+    }
+}
+
+#[test]
+fn alignment() {
+    let mut buffer = Buffer::<1024>::new();
+    let mut res = RingResource::new(&mut buffer.0);
+    unsafe {
+        let a = res.try_allocate(bytes(3)).unwrap().as_ptr() as *mut ();
+        let layout = Layout::from_size_align(8, 128).unwrap();
+        let b = res.try_allocate(layout).unwrap().as_ptr() as *mut ();
+        assert_eq!(b as usize % 128, 0);
+        // the padding before `b` goes with `a`
+        res.deallocate(a, bytes(3));
+        assert_eq!(res.used(), HEADER + 8);
+        res.deallocate(b, layout);
+    }
+    assert!(res.is_empty());
+}
+
+#[test]
+fn grow_newest() {
+    let mut buffer = Buffer::<128>::new();
+    let mut res = RingResource::new(&mut buffer.0);
+    unsafe {
+        let a = res.try_allocate(bytes(8)).unwrap().as_ptr() as *mut ();
+        assert!(res.try_grow_in_place(a, bytes(8), bytes(32)));
+        let b = res.try_allocate(bytes(8)).unwrap().as_ptr() as *mut ();
+        assert_eq!(b as usize - a as usize, 32 + HEADER);
+        assert!(!res.try_grow_in_place(a, bytes(32), bytes(40)));
+        // still fits its record
+        assert!(res.try_grow_in_place(b, bytes(1), bytes(8)));
+        assert!(res.try_grow_in_place(b, bytes(8), bytes(80)));
+        assert_eq!(res.used(), res.capacity());
+        assert!(!res.try_grow_in_place(b, bytes(80), bytes(88)));
+    }
+}
+
+#[test]
+fn streaming() {
+    let mut buffer = Buffer::<4096>::new();
+    let range = buffer.0.as_ptr_range();
+    let mut res = RingResource::new(&mut buffer.0);
+    let mut rng = Lcg(3);
+    // live messages in allocation order: address, layout and fill byte
+    let mut live: VecDeque<(usize, Layout, u8)> = VecDeque::new();
+    let mut failures = 0;
+    unsafe {
+        for step in 0..20_000 {
+            if live.is_empty() || rng.next(2) == 0 {
+                let layout = Layout::from_size_align(rng.next(300), 1 << rng.next(5)).unwrap();
+                let p = match res.try_allocate(layout) {
+                    Ok(p) => p.as_ptr() as usize,
+                    Err(_) => {
+                        failures += 1;
+                        continue;
+                    }
+                };
+                let size = layout.size();
+                assert_eq!(p % layout.align(), 0);
+                assert!(range.start as usize <= p && p + size <= range.end as usize);
+                for &(q, other, _) in &live {
+                    assert!(p + size.max(1) <= q || q + other.size().max(1) <= p);
+                }
+                let fill = step as u8;
+                (p as *mut u8).write_bytes(fill, size);
+                live.push_back((p, layout, fill));
+            } else {
+                // mostly the oldest, sometimes one a little newer
+                let index = rng.next(live.len().min(3));
+                let (p, layout, fill) = live.remove(index).unwrap();
+                let block = core::slice::from_raw_parts(p as *const u8, layout.size());
+                assert!(block.iter().all(|&b| b == fill));
+                res.deallocate(p as *mut (), layout);
+            }
+        }
+        for (p, layout, _) in live {
+            res.deallocate(p as *mut (), layout);
+        }
+    }
+    assert!(failures > 0);
+    assert!(res.is_empty());
+}
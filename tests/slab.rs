@@ -0,0 +1,142 @@
+use core::{alloc::Layout, cell::RefCell};
+use std::rc::Rc;
+use typenum::U16;
+use yaap::a::{self, Allocator, SlabResource, SlabStats, SystemResource};
+
+mod deque;
+mod tracked;
+use deque::Seque;
+use tracked::Tracked;
+
+fn slab(block: Layout, blocks_per_slab: usize) -> Rc<RefCell<SlabResource>> {
+    let upstream = Allocator::new(Tracked::new(SystemResource::default()));
+    Rc::new(RefCell::new(SlabResource::new(
+        block,
+        blocks_per_slab,
+        upstream,
+    )))
+}
+
+#[test]
+fn slab_rounded_to_power_of_two() {
+    let upstream = Allocator::new(Tracked::new(SystemResource::default()));
+    let res = SlabResource::for_type::<u64>(4, upstream);
+    assert_eq!(res.block_layout(), Layout::new::<u64>());
+    // a 16 byte header and 4 blocks round up to 64 bytes, which hold 6 blocks
+    let stats = res.stats();
+    assert_eq!(stats.blocks_per_slab, 6);
+    assert_eq!(
+        (stats.slabs, stats.used_blocks, stats.free_blocks()),
+        (0, 0, 0)
+    );
+}
+
+#[test]
+fn small_blocks_widened() {
+    let res = slab(Layout::new::<u8>(), 16);
+    assert_eq!(res.borrow().block_layout(), Layout::new::<usize>());
+}
+
+#[test]
+fn blocks_are_reused() {
+    let alloc = Allocator::new(slab(Layout::new::<u64>(), 4));
+    unsafe {
+        let p = alloc.try_allocate::<u64>(1).unwrap().as_ptr();
+        alloc.deallocate(p as *mut u64, 1);
+        let q = alloc.try_allocate::<u64>(1).unwrap().as_ptr();
+        assert_eq!(p, q);
+        alloc.deallocate(q as *mut u64, 1);
+    }
+}
+
+#[test]
+fn occupancy() {
+    let res = slab(Layout::new::<u64>(), 4);
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let blocks: Vec<_> = (0..7)
+            .map(|_| alloc.try_allocate::<u64>(1).unwrap().as_ptr() as *mut u64)
+            .collect();
+        assert_eq!(res.borrow().occupancy().collect::<Vec<_>>(), [1, 6]);
+        assert_eq!(
+            res.borrow().stats(),
+            SlabStats {
+                slabs: 2,
+                blocks_per_slab: 6,
+                used_blocks: 7
+            }
+        );
+        assert_eq!(res.borrow().stats().free_blocks(), 5);
+        for &p in &blocks[..3] {
+            alloc.deallocate(p, 1);
+        }
+        assert_eq!(res.borrow().occupancy().collect::<Vec<_>>(), [1, 3]);
+        for &p in &blocks[3..] {
+            alloc.deallocate(p, 1);
+        }
+        assert_eq!(res.borrow().stats().used_blocks, 0);
+    }
+}
+
+#[test]
+fn refuses_what_does_not_fit() {
+    let alloc = Allocator::new(slab(Layout::new::<u64>(), 4));
+    unsafe {
+        let err = alloc.try_allocate::<u64>(2).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::UnsupportedSize);
+        assert!(err.resource().ends_with("SlabResource"));
+        let layout = Layout::from_size_align(8, 16).unwrap();
+        let err = alloc.try_allocate_layout(layout).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Misaligned);
+        // anything smaller fits
+        let p = alloc.try_allocate::<u16>(3).unwrap();
+        assert!(alloc.try_grow_in_place(p.as_ptr() as *mut u16, 3, 4));
+        assert!(!alloc.try_grow_in_place(p.as_ptr() as *mut u16, 4, 5));
+        alloc.deallocate(p.as_ptr() as *mut u16, 4);
+    }
+}
+
+#[test]
+fn aligned_blocks() {
+    let alloc = Allocator::new(slab(Layout::from_size_align(24, 64).unwrap(), 10));
+    unsafe {
+        for _ in 0..30 {
+            let p = alloc.try_allocate::<u8>(24).unwrap().as_ptr() as usize;
+            assert_eq!(p % 64, 0);
+        }
+    }
+}
+
+#[test]
+fn seque_nodes() {
+    let res = slab(Layout::new::<[usize; 16]>(), 8);
+    {
+        let mut c = Seque::<usize, U16>::with_capacity_in(1, Allocator::new(res.clone()));
+        for i in 0..100 {
+            c.push_back(i);
+        }
+        assert!((0..100).all(|i| c[i] == i));
+        // a data array for every 16 values, and a node for all but the first, which is inline
+        assert_eq!(res.borrow().stats().used_blocks, 7 + 6);
+    }
+    assert_eq!(res.borrow().stats().used_blocks, 0);
+}
+
+#[test]
+fn release_returns_every_slab() {
+    let upstream = Tracked::new(SystemResource::default());
+    let res = Rc::new(RefCell::new(SlabResource::for_type::<u64>(
+        4,
+        Allocator::new(upstream.clone()),
+    )));
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        for _ in 0..20 {
+            alloc.try_allocate::<u64>(1).unwrap();
+        }
+    }
+    assert_eq!(upstream.borrow().count(), 4 * 64);
+    unsafe { res.borrow_mut().release() };
+    assert_eq!(upstream.borrow().count(), 0);
+    assert_eq!(res.borrow().stats().slabs, 0);
+}
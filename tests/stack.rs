@@ -1,4 +1,7 @@
-use core::{fmt, ptr::NonNull};
+// generic-array 0.14.8+ deprecates its 0.14 API in favour of 1.x
+#![allow(deprecated)]
+
+use core::{alloc::Layout, fmt, ptr::NonNull};
 use generic_array::{ArrayLength, GenericArray};
 use typenum::{U1024, U127, U128, U16};
 use yaap::{
@@ -42,22 +45,39 @@ mod stack_alloc {
     where
         N: ArrayLength<u8>,
     {
-        unsafe fn allocate_bytes(&mut self, size: usize, align: usize) -> a::PtrUninit<()> {
-            if self.used + size > N::USIZE {
-                return None;
+        unsafe fn try_allocate(&mut self, layout: Layout) -> a::AllocResult<()> {
+            let (size, align) = (layout.size(), layout.align());
+            let ptr = self.data.0.as_mut_ptr().add(self.used);
+            let padding = ptr.align_offset(align);
+            if self.used + padding + size > N::USIZE {
+                return Err(a::AllocError::exhausted::<Self>(layout));
             }
-            let start_ptr = self.data.0.as_mut_ptr();
-            let ptr = {
-                let ptr = start_ptr.add(self.used);
-                let offset = ptr.align_offset(align);
-                ptr.add(offset)
-            };
-            self.used += ptr as usize - start_ptr as usize; // ptr.offset_from(start_ptr)
-            self.used += size;
-            Some(NonNull::new_unchecked(ptr as *mut _))
+            self.used += padding + size;
+            Ok(NonNull::new_unchecked(ptr.add(padding) as *mut _))
+        }
+
+        /// The data starts out zeroed and is never handed out twice, so it needs no memset
+        unsafe fn allocate_zeroed(&mut self, layout: Layout) -> a::AllocResult<()> {
+            self.try_allocate(layout)
         }
 
         unsafe fn deallocate_bytes(&mut self, _pointer: *mut (), _size: usize, _align: usize) {}
+
+        unsafe fn try_grow_in_place(
+            &mut self,
+            pointer: *mut (),
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> bool {
+            let end = self.data.0.as_ptr().add(self.used);
+            let extra = new_layout.size() - old_layout.size();
+            if (pointer as *const u8).add(old_layout.size()) != end || self.used + extra > N::USIZE
+            {
+                return false;
+            }
+            self.used += extra;
+            true
+        }
     }
 
     impl<N> StackResource<N>
@@ -98,17 +118,118 @@ fn none() {
 }
 
 #[test]
-#[should_panic]
+#[should_panic(expected = "resource exhausted")]
 fn single_fail() {
+    // fails here because the first node's data doesn't fit
     let mut c = Seque::<usize, U16>::with_capacity_in(
         1,
         Allocator::new(Tracked::new(StackResource::<U127>::new())),
     );
-    // fails here because allocate returned None, but the capacity wasn't updated
-    // TODO: propogate error to caller
     c.push_back(4);
 }
 
+#[test]
+fn single_try_fail() {
+    let res = Tracked::new(StackResource::<U127>::new());
+    let c = Seque::<usize, U16>::try_with_capacity_in(1, Allocator::new(res.clone()));
+    assert_eq!(c.err().unwrap().kind(), a::AllocErrorKind::Exhausted);
+    assert_eq!(res.borrow().count(), 0);
+}
+
+#[test]
+fn push_until_full() {
+    let res = Tracked::new(StackResource::<U1024>::new());
+    let mut c = Seque::<usize, U16>::with_capacity_in(1, Allocator::new(res.clone()));
+    let mut pushed = 0;
+    let err = loop {
+        match c.try_push_back(pushed) {
+            Ok(()) => pushed += 1,
+            Err(e) => break e,
+        }
+    };
+    assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+    assert!(pushed >= 16);
+    assert!((0..pushed).all(|i| c[i] == i));
+}
+
+#[test]
+fn exhausted_error() {
+    let res = Tracked::new(StackResource::<U128>::new());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u64>(16).expect("fits exactly");
+        let err = alloc.try_allocate::<u64>(1).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert_eq!(err.layout(), Some(Layout::new::<u64>()));
+        assert!(err.resource().contains("StackResource"));
+        assert!(err
+            .to_string()
+            .ends_with("could not allocate 8 bytes aligned to 8: resource exhausted"));
+        alloc.deallocate(p.as_ptr(), 16);
+    }
+}
+
+#[test]
+fn overflow_error() {
+    let alloc = Allocator::new(Tracked::new(StackResource::<U128>::new()));
+    let err = unsafe { alloc.try_allocate::<u64>(usize::MAX) }.unwrap_err();
+    assert_eq!(err.kind(), a::AllocErrorKind::UnsupportedSize);
+    assert_eq!(err.layout(), None);
+}
+
+#[test]
+fn grow_last_in_place() {
+    let res = Tracked::new(StackResource::<U128>::new());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u32>(4).unwrap().as_ptr() as *mut u32;
+        for i in 0..4 {
+            p.add(i).write(i as u32);
+        }
+        assert!(alloc.try_grow_in_place(p, 4, 8));
+        let q = alloc.grow(p, 8, 16).unwrap().as_ptr() as *mut u32;
+        assert_eq!(p, q);
+        assert_eq!(res.borrow().used(), 64);
+        assert_eq!(res.borrow().count(), 64);
+        assert!(alloc.grow(q, 16, 64).is_err());
+        assert!((0..4).all(|i| *q.add(i) == i as u32));
+        alloc.deallocate(q, 16);
+    }
+}
+
+#[test]
+fn grow_moves() {
+    let res = Tracked::new(StackResource::<U128>::new());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.try_allocate::<u8>(4).unwrap().as_ptr() as *mut u8;
+        p.write_bytes(7, 4);
+        let other = alloc.try_allocate::<u8>(1).unwrap();
+        assert!(!alloc.try_grow_in_place(p, 4, 8));
+        let q = alloc.grow_zeroed(p, 4, 8).unwrap().as_ptr() as *mut u8;
+        assert_ne!(p, q);
+        let bytes = core::slice::from_raw_parts(q, 8);
+        assert_eq!(bytes, &[7, 7, 7, 7, 0, 0, 0, 0]);
+        let r = alloc.shrink(q, 8, 2).unwrap().as_ptr() as *mut u8;
+        assert_eq!(core::slice::from_raw_parts(r, 2), &[7, 7]);
+        assert_eq!(res.borrow().count(), 3);
+        alloc.deallocate(r, 2);
+        alloc.deallocate(other.as_ptr(), 1);
+    }
+}
+
+#[test]
+fn zeroed_without_memset() {
+    let res = Tracked::new(StackResource::<U128>::new());
+    let alloc = Allocator::new(res.clone());
+    unsafe {
+        let p = alloc.allocate_zeroed::<u64>(4).unwrap().as_ptr() as *mut u64;
+        assert!((0..4).all(|i| *p.add(i) == 0));
+        assert_eq!(res.borrow().count(), 32);
+        alloc.deallocate(p, 4);
+    }
+}
+
 #[test]
 fn single() {
     let res = Tracked::new(StackResource::<U128>::new());
@@ -127,7 +248,7 @@ fn reallocate() {
     for i in 0..c.node_array_len() + 1 {
         c.push_back(2 + i);
     }
-    assert_eq!(res.borrow().used(), 672);
+    assert_eq!(res.borrow().used(), 272);
 }
 #[test]
 fn large_allocate() {
@@ -137,7 +258,7 @@ fn large_allocate() {
     for i in 0..Seq16::NODE_ARRAY_LEN + 2 {
         c.push_back(2 + i);
     }
-    assert_eq!(res.borrow().used(), 672);
+    assert_eq!(res.borrow().used(), 272);
     println!("{}", &**res.borrow())
 }
 
@@ -180,7 +301,7 @@ fn test_drop() {
         for _i in 0..Seq16::NODE_ARRAY_LEN + 2 {
             c.push_back(Test::new());
         }
-        assert_eq!(res.borrow().used(), 32);
+        assert_eq!(res.borrow().used(), 16);
         assert_eq!(unsafe {COUNTER}, 18);
     }
     assert_eq!(unsafe {COUNTER}, 0);
@@ -0,0 +1,94 @@
+use core::{alloc::Layout, mem::MaybeUninit};
+use yaap::{
+    a::{self, StackEnd},
+    prelude::*,
+};
+
+mod fixtures;
+use fixtures::{bytes, Buffer};
+
+#[test]
+fn reclaims_top() {
+    let mut buffer = Buffer::<256>::new();
+    let mut res = a::StackResource::new(&mut buffer.0);
+    unsafe {
+        let a = res.try_allocate(bytes(10)).unwrap().as_ptr();
+        let b = res.try_allocate(Layout::new::<u64>()).unwrap().as_ptr();
+        let c = res.try_allocate(bytes(100)).unwrap().as_ptr();
+        // a header word before each block, and padding the `u64` to 8
+        assert_eq!(res.used_low(), 8 + 10 + 6 + 8 + 8 + 8 + 100);
+        res.deallocate(c as *mut (), bytes(100));
+        assert_eq!(res.used_low(), 8 + 10 + 6 + 8 + 8);
+        let d = res.try_allocate(bytes(200)).unwrap().as_ptr();
+        assert_eq!(c, d);
+        res.deallocate(d as *mut (), bytes(200));
+        res.deallocate(b as *mut (), Layout::new::<u64>());
+        res.deallocate(a as *mut (), bytes(10));
+    }
+    assert_eq!(res.used_low(), 0);
+    assert_eq!(res.remaining(), 256);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "stack freed out of order")]
+fn out_of_order() {
+    let mut buffer = Buffer::<64>::new();
+    let mut res = a::StackResource::new(&mut buffer.0);
+    unsafe {
+        let a = res.try_allocate(bytes(8)).unwrap().as_ptr();
+        let _b = res.try_allocate(bytes(8)).unwrap();
+        res.deallocate(a as *mut (), bytes(8));
+    }
+}
+
+#[test]
+fn double_ended() {
+    let mut buffer = Buffer::<128>::new();
+    let range = buffer.0.as_ptr_range();
+    let mut res = a::StackResource::new(&mut buffer.0);
+    unsafe {
+        let long = res.try_allocate(bytes(40)).unwrap().as_ptr() as *const MaybeUninit<u8>;
+        // after its header
+        assert_eq!(long, range.start.wrapping_add(8));
+        res.set_end(StackEnd::High);
+        assert_eq!(res.end(), StackEnd::High);
+        let scratch = res.try_allocate(bytes(24)).unwrap().as_ptr() as *const MaybeUninit<u8>;
+        // before its header
+        assert_eq!(scratch.wrapping_add(24 + 8), range.end);
+        let aligned = res.try_allocate(Layout::new::<u64>()).unwrap().as_ptr();
+        assert_eq!(aligned as usize % 8, 0);
+        assert!((aligned as *const MaybeUninit<u8>) < scratch);
+        assert_eq!(res.used_low() + res.used_high() + res.remaining(), 128);
+
+        // the ends meet
+        let err = res.try_allocate(bytes(res.remaining() + 1)).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert!(err.resource().contains("StackResource"));
+
+        // frees find their end by address
+        res.deallocate(aligned as *mut (), Layout::new::<u64>());
+        res.deallocate(scratch as *mut (), bytes(24));
+        assert_eq!(res.used_high(), 0);
+        res.deallocate(long as *mut (), bytes(40));
+    }
+    assert_eq!(res.remaining(), 128);
+}
+
+#[test]
+fn grows_top_in_place() {
+    let mut buffer = Buffer::<128>::new();
+    let mut res = a::StackResource::new(&mut buffer.0);
+    unsafe {
+        let a = res.try_allocate(bytes(8)).unwrap().as_ptr() as *mut ();
+        assert!(res.try_grow_in_place(a, bytes(8), bytes(32)));
+        res.set_end(StackEnd::High);
+        // takes the last 24 bytes with its header
+        res.try_allocate(bytes(16)).unwrap();
+        assert!(!res.try_grow_in_place(a, bytes(32), bytes(100)));
+        assert!(res.try_grow_in_place(a, bytes(32), bytes(96)));
+        assert_eq!(res.remaining(), 0);
+    }
+    unsafe { res.reset() };
+    assert_eq!(res.remaining(), 128);
+}
@@ -0,0 +1,99 @@
+use core::{mem::MaybeUninit, ptr::NonNull};
+use std::thread;
+use yaap::{
+    a::{SyncAllocator, SystemResource},
+    prelude::*,
+};
+
+mod tracked;
+use tracked::Tracked;
+
+struct Buffer {
+    data: NonNull<MaybeUninit<u64>>,
+    len: usize,
+    alloc: SyncAllocator,
+}
+
+impl Buffer {
+    fn filled_in(len: usize, value: u64, alloc: SyncAllocator) -> Self {
+        let data = unsafe { alloc.allocate::<u64>(len) }.expect("allocation");
+        for i in 0..len {
+            unsafe { data.as_ptr().add(i).write(MaybeUninit::new(value)) };
+        }
+        Self { data, len, alloc }
+    }
+
+    fn sum(&self) -> u64 {
+        (0..self.len)
+            .map(|i| unsafe { (*self.data.as_ptr().add(i)).assume_init() })
+            .sum()
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe { self.alloc.deallocate(self.data.as_ptr(), self.len) }
+    }
+}
+
+// Safety: the buffer uniquely owns its data
+unsafe impl Send for Buffer {}
+
+impl AllocatorAwareContainer<SyncAllocator> for Buffer {
+    fn allocator(&self) -> SyncAllocator {
+        self.alloc.clone()
+    }
+}
+
+#[test]
+fn shared_between_threads() {
+    let res = Tracked::new_sync(SystemResource::default());
+    let alloc = SyncAllocator::new(res.clone());
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let alloc = alloc.clone();
+            thread::spawn(move || {
+                for len in 1..64 {
+                    let b = Buffer::filled_in(len, t, alloc.clone());
+                    assert_eq!(b.sum(), t * len as u64);
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(res.lock().unwrap().count(), 0);
+}
+
+#[test]
+fn container_sent_to_thread() {
+    let res = Tracked::new_sync(SystemResource::default());
+    let alloc = SyncAllocator::new(res.clone());
+    let b = Buffer::filled_in(8, 3, alloc.clone());
+    assert!(b.allocator() == alloc);
+    assert_eq!(res.lock().unwrap().count(), 64);
+    let sum = thread::spawn(move || b.sum()).join().unwrap();
+    assert_eq!(sum, 24);
+    assert_eq!(res.lock().unwrap().count(), 0);
+}
+
+#[test]
+fn try_allocate_overflow() {
+    let alloc = SyncAllocator::new(Tracked::new_sync(SystemResource::default()));
+    let err = unsafe { alloc.try_allocate::<u64>(usize::MAX) }.unwrap_err();
+    assert!(err.resource().contains("SyncAllocator"));
+}
+
+#[test]
+fn zeroed_across_threads() {
+    let alloc = SyncAllocator::new(Tracked::new_sync(SystemResource::default()));
+    let p = unsafe { alloc.allocate_zeroed::<u32>(256) }.unwrap();
+    let addr = p.as_ptr() as usize;
+    let zeroed = thread::spawn(move || {
+        let p = addr as *const u32;
+        (0..256).all(|i| unsafe { *p.add(i) } == 0)
+    });
+    assert!(zeroed.join().unwrap());
+    unsafe { alloc.deallocate(p.as_ptr(), 256) };
+}
@@ -0,0 +1,167 @@
+use core::{alloc::Layout, mem::MaybeUninit};
+use yaap::{
+    a::{self, TlsfResource},
+    prelude::*,
+};
+
+mod fixtures;
+use fixtures::{bytes, Buffer, Lcg};
+
+/// A block header is two words.
+const HEADER: usize = 2 * core::mem::size_of::<usize>();
+
+#[test]
+fn one_free_block() {
+    let mut pool = Buffer::<4096>::new();
+    let res = TlsfResource::new(&mut pool.0);
+    let stats = res.stats();
+    // a pool link, a block header and the closing sentinel
+    assert_eq!(stats.free, 4096 - 3 * HEADER);
+    assert_eq!(
+        (stats.free_blocks, stats.used_blocks, stats.used),
+        (1, 0, 0)
+    );
+    assert_eq!(stats.largest_free, stats.free);
+}
+
+#[test]
+fn coalesces_both_neighbours() {
+    let mut pool = Buffer::<4096>::new();
+    let mut res = TlsfResource::new(&mut pool.0);
+    let initial = res.stats();
+    unsafe {
+        let blocks: Vec<_> = (0..4)
+            .map(|_| res.try_allocate(bytes(100)).unwrap().as_ptr() as *mut ())
+            .collect();
+        assert_eq!(res.stats().used_blocks, 4);
+        res.deallocate(blocks[0], bytes(100));
+        res.deallocate(blocks[2], bytes(100));
+        assert_eq!(res.stats().free_blocks, 3);
+        // merges with the free blocks on either side
+        res.deallocate(blocks[1], bytes(100));
+        assert_eq!(res.stats().free_blocks, 2);
+        // and with the tail
+        res.deallocate(blocks[3], bytes(100));
+    }
+    assert_eq!(res.stats(), initial);
+}
+
+#[test]
+fn reuses_freed_block() {
+    let mut pool = Buffer::<4096>::new();
+    let mut res = TlsfResource::new(&mut pool.0);
+    unsafe {
+        let a = res.try_allocate(bytes(200)).unwrap().as_ptr();
+        let _b = res.try_allocate(bytes(8)).unwrap();
+        res.deallocate(a as *mut (), bytes(200));
+        let c = res.try_allocate(bytes(150)).unwrap().as_ptr();
+        assert_eq!(a, c);
+    }
+}
+
+#[test]
+fn add_pool() {
+    let mut first = Buffer::<1024>::new();
+    let mut second = Buffer::<8192>::new();
+    let range = second.0.as_ptr_range();
+    let mut res = TlsfResource::new(&mut first.0);
+    unsafe {
+        let err = res.try_allocate(bytes(4000)).unwrap_err();
+        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+        assert!(err.resource().contains("TlsfResource"));
+        assert!(res.add_pool(&mut second.0));
+        let p = res.try_allocate(bytes(4000)).unwrap().as_ptr() as *const MaybeUninit<u8>;
+        assert!(range.contains(&p));
+        assert_eq!(res.stats().free_blocks, 2);
+    }
+    let mut tiny = [MaybeUninit::uninit(); 16];
+    assert!(!res.add_pool(&mut tiny));
+}
+
+#[test]
+fn large_alignment() {
+    let mut pool = Buffer::<8192>::new();
+    let mut res = TlsfResource::new(&mut pool.0);
+    unsafe {
+        for align in [32, 64, 256, 1024] {
+            res.try_allocate(bytes(24)).unwrap();
+            let layout = Layout::from_size_align(40, align).unwrap();
+            let p = res.try_allocate(layout).unwrap().as_ptr() as usize;
+            assert_eq!(p % align, 0);
+        }
+    }
+}
+
+#[test]
+fn grow_in_place() {
+    let mut pool = Buffer::<4096>::new();
+    let mut res = TlsfResource::new(&mut pool.0);
+    unsafe {
+        let a = res.try_allocate(bytes(64)).unwrap().as_ptr() as *mut ();
+        assert!(res.try_grow_in_place(a, bytes(64), bytes(1000)));
+        let b = res.try_allocate(bytes(64)).unwrap().as_ptr() as *mut ();
+        // payloads are rounded up to a multiple of 16
+        assert_eq!(b as usize - a as usize, 1008 + HEADER);
+        assert!(!res.try_grow_in_place(a, bytes(1000), bytes(1100)));
+        // still fits its block
+        assert!(res.try_grow_in_place(a, bytes(1000), bytes(1008)));
+        res.deallocate(b, bytes(64));
+        assert!(res.try_grow_in_place(a, bytes(1008), bytes(2000)));
+        assert_eq!(res.stats().used_blocks, 1);
+    }
+}
+
+#[test]
+fn shadow_model() {
+    let mut first = Buffer::<16384>::new();
+    let mut second = Buffer::<4096>::new();
+    let pools = [first.0.as_ptr_range(), second.0.as_ptr_range()];
+    let mut res = TlsfResource::new(&mut first.0);
+    res.add_pool(&mut second.0);
+    let initial = res.stats();
+    let mut rng = Lcg(42);
+    // every live allocation: address, layout and the byte it was filled with
+    let mut live: Vec<(usize, Layout, u8)> = Vec::new();
+    unsafe {
+        for step in 0..20_000 {
+            if live.is_empty() || rng.next(5) < 3 {
+                let size = if rng.next(10) == 0 {
+                    rng.next(3000)
+                } else {
+                    rng.next(200)
+                };
+                let layout = Layout::from_size_align(size, 1 << rng.next(8)).unwrap();
+                let p = match res.try_allocate(layout) {
+                    Ok(p) => p.as_ptr() as usize,
+                    Err(err) => {
+                        assert_eq!(err.kind(), a::AllocErrorKind::Exhausted);
+                        continue;
+                    }
+                };
+                assert_eq!(p % layout.align(), 0);
+                assert!(pools
+                    .iter()
+                    .any(|pool| { pool.start as usize <= p && p + size <= pool.end as usize }));
+                for &(q, other, _) in &live {
+                    assert!(p + size.max(1) <= q || q + other.size().max(1) <= p);
+                }
+                let fill = step as u8;
+                (p as *mut u8).write_bytes(fill, size);
+                live.push((p, layout, fill));
+            } else {
+                let (p, layout, fill) = live.swap_remove(rng.next(live.len()));
+                let block = core::slice::from_raw_parts(p as *const u8, layout.size());
+                assert!(block.iter().all(|&b| b == fill));
+                res.deallocate(p as *mut (), layout);
+            }
+            let stats = res.stats();
+            assert_eq!(stats.used_blocks, live.len());
+            // coalescing leaves no two free blocks side by side
+            assert!(stats.free_blocks <= stats.used_blocks + pools.len());
+        }
+        for (p, layout, _) in live {
+            res.deallocate(p as *mut (), layout);
+        }
+    }
+    assert_eq!(res.stats(), initial);
+}
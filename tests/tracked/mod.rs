@@ -1,5 +1,11 @@
-use core::{cell::RefCell, ops::Deref};
-use std::rc::Rc;
+// shared by several test binaries, each using only part of it
+#![allow(dead_code)]
+
+use core::{alloc::Layout, cell::RefCell, ops::Deref};
+use std::{
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
 use yaap::{a, prelude::*};
 
 #[derive(Debug)]
@@ -9,19 +15,66 @@ impl<T> MemoryResource for Tracked<T>
 where
     T: MemoryResource,
 {
-    unsafe fn allocate_bytes(&mut self, size: usize, align: usize) -> a::PtrUninit<()> {
-        let p = self.0.allocate_bytes(size, align);
-        if p.is_some() {
-            self.1 += size;
+    unsafe fn try_allocate(&mut self, layout: Layout) -> a::AllocResult<()> {
+        let p = self.0.try_allocate(layout);
+        if p.is_ok() {
+            self.1 += layout.size();
+        }
+        p
+    }
+
+    unsafe fn allocate_zeroed(&mut self, layout: Layout) -> a::AllocResult<()> {
+        let p = self.0.allocate_zeroed(layout);
+        if p.is_ok() {
+            self.1 += layout.size();
         }
         p
     }
 
-    unsafe fn deallocate_bytes(&mut self, pointer: *mut (), size: usize, align: usize) {
+    unsafe fn deallocate(&mut self, pointer: *mut (), layout: Layout) {
         if !pointer.is_null() {
-            self.1 -= size;
+            self.1 -= layout.size();
+        }
+        self.0.deallocate(pointer, layout)
+    }
+
+    unsafe fn try_grow_in_place(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> bool {
+        let grown = self.0.try_grow_in_place(pointer, old_layout, new_layout);
+        if grown {
+            self.1 += new_layout.size() - old_layout.size();
+        }
+        grown
+    }
+
+    unsafe fn grow(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> a::AllocResult<()> {
+        let p = self.0.grow(pointer, old_layout, new_layout);
+        if p.is_ok() {
+            self.1 += new_layout.size() - old_layout.size();
         }
-        self.0.deallocate_bytes(pointer, size, align)
+        p
+    }
+
+    unsafe fn shrink(
+        &mut self,
+        pointer: *mut (),
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> a::AllocResult<()> {
+        let p = self.0.shrink(pointer, old_layout, new_layout);
+        if p.is_ok() {
+            self.1 -= old_layout.size() - new_layout.size();
+        }
+        p
     }
 }
 
@@ -38,6 +91,10 @@ where
     pub fn new(inner: T) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Tracked(inner, 0)))
     }
+
+    pub fn new_sync(inner: T) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Tracked(inner, 0)))
+    }
 }
 
 impl<T> Deref for Tracked<T> {